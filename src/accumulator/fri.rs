@@ -0,0 +1,632 @@
+// src/accumulator/fri.rs
+
+use super::reed_solomon::{
+    scratch, write_bytes, write_elements, write_len, ProofError, ProofReader,
+};
+use crate::crypto::{
+    field::FieldElement, merkle::MerkleTree, poly::lagrange_interpolate, transcript::Transcript,
+};
+
+// 7 generates the multiplicative group of the Mersenne-31 field
+const GENERATOR: u64 = 7;
+// Prime factors of p - 1 = 2·3²·7·11·31·151·331, smallest first. The
+// two-adicity is only 1, so FRI folds by odd factors as well.
+const ORDER_FACTORS: [usize; 8] = [2, 3, 3, 7, 11, 31, 151, 331];
+// Soundness of a low-degree proof over the default domain, in bits. Every
+// challenge (the DEEP combination, the degree correction and one per fold)
+// is a base-field element, and each is bad for a cheating prover with
+// probability about |D|/p, so the draws alone fail with probability about
+// draws·|D|/p: 5·462/p ≈ 2^-19.8 for the default domain and schedule (see
+// `challenge_soundness_bits`). `query_count` holds the queries to 2^-19,
+// for under 2^-18 in total. More needs challenges from an extension field.
+pub const SECURITY_BITS: u32 = 18;
+
+// One layer of a query: the fiber the queried point belongs to and its Merkle path
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriOpening {
+    values: Vec<FieldElement>,
+    path: Vec<Vec<u8>>,
+}
+
+// The first layer is the codeword the caller has already committed to, so
+// the proof only carries the folded layers: one root per layer that is
+// folded again, and per query one opening on each of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriProof {
+    fold_factors: Vec<usize>,
+    layer_roots: Vec<[u8; 32]>,
+    final_value: FieldElement,
+    queries: Vec<Vec<FriOpening>>,
+}
+
+impl FriProof {
+    pub fn fold_factors(&self) -> &[usize] {
+        &self.fold_factors
+    }
+
+    // Length of `to_bytes()`
+    pub fn size_bytes(&self) -> usize {
+        let path = |path: &[Vec<u8>]| 4 + path.iter().map(|node| 4 + node.len()).sum::<usize>();
+        let queries: usize = self
+            .queries
+            .iter()
+            .map(|query| {
+                4 + query
+                    .iter()
+                    .map(|opening| 4 + 8 * opening.values.len() + path(&opening.path))
+                    .sum::<usize>()
+            })
+            .sum();
+        4 + 8 * self.fold_factors.len() + 4 + 36 * self.layer_roots.len() + 12 + 4 + queries
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_len(&mut out, self.fold_factors.len());
+        for &arity in &self.fold_factors {
+            out.extend_from_slice(&(arity as u64).to_le_bytes());
+        }
+        write_len(&mut out, self.layer_roots.len());
+        for root in &self.layer_roots {
+            write_bytes(&mut out, root);
+        }
+        write_elements(&mut out, &[self.final_value]);
+        write_len(&mut out, self.queries.len());
+        for query in &self.queries {
+            write_len(&mut out, query.len());
+            for opening in query {
+                write_elements(&mut out, &opening.values);
+                write_len(&mut out, opening.path.len());
+                for node in &opening.path {
+                    write_bytes(&mut out, node);
+                }
+            }
+        }
+        out
+    }
+
+    // Inverse of `to_bytes`, as part of `RSProof::from_bytes`
+    pub(super) fn read(reader: &mut ProofReader) -> Result<FriProof, ProofError> {
+        let factor_count = reader.read_len()?;
+        let mut fold_factors = Vec::with_capacity(factor_count);
+        for _ in 0..factor_count {
+            fold_factors.push(reader.read_u64()? as usize);
+        }
+        let root_count = reader.read_len()?;
+        let mut layer_roots = Vec::with_capacity(root_count);
+        for _ in 0..root_count {
+            layer_roots.push(reader.read_node()?);
+        }
+        let final_value = match reader.read_elements()?[..] {
+            [value] => value,
+            ref other => {
+                return Err(ProofError::UnexpectedLength {
+                    expected: 1,
+                    actual: other.len(),
+                })
+            }
+        };
+        let query_count = reader.read_len()?;
+        let mut queries = Vec::with_capacity(query_count);
+        for _ in 0..query_count {
+            let layer_count = reader.read_len()?;
+            let mut query = Vec::with_capacity(layer_count);
+            for _ in 0..layer_count {
+                let values = reader.read_elements()?;
+                let path = reader.read_path()?;
+                query.push(FriOpening { values, path });
+            }
+            queries.push(query);
+        }
+        Ok(FriProof {
+            fold_factors,
+            layer_roots,
+            final_value,
+            queries,
+        })
+    }
+}
+
+// Prime factors of `size`, smallest first, or `None` when `size` doesn't
+// divide p - 1 and so has no subgroup of that order
+fn order_factors(size: usize) -> Option<Vec<usize>> {
+    if size == 0 || !(FieldElement::MODULUS - 1).is_multiple_of(size as u64) {
+        return None;
+    }
+    let mut rest = size;
+    let mut factors = Vec::new();
+    for &factor in &ORDER_FACTORS {
+        if rest.is_multiple_of(factor) {
+            rest /= factor;
+            factors.push(factor);
+        }
+    }
+    Some(factors)
+}
+
+// Folding arities that take a polynomial with `coefficients` coefficients
+// over a domain of `domain_size` points down to a constant: the factors of
+// the domain size with the smallest product covering the coefficients,
+// smallest first. What is left of the domain is the blowup, which must be
+// at least 2. `None` when there is no such schedule.
+pub fn fold_schedule(domain_size: usize, coefficients: usize) -> Option<Vec<usize>> {
+    let factors = order_factors(domain_size)?;
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    for mask in 1u32..(1 << factors.len()) {
+        let chosen: Vec<usize> = (0..factors.len())
+            .filter(|&i| mask & (1 << i) != 0)
+            .map(|i| factors[i])
+            .collect();
+        let product: usize = chosen.iter().product();
+        if product >= coefficients
+            && product < domain_size
+            && best.as_ref().is_none_or(|(least, _)| product < *least)
+        {
+            best = Some((product, chosen));
+        }
+    }
+    best.map(|(_, schedule)| schedule)
+}
+
+// Whole bits of soundness `draws` base-field challenges give over a domain
+// of `domain_size` points: floor(log2(p / (draws·|D|))). An aggregate adds
+// one draw per combined commitment to the proof's own.
+pub fn challenge_soundness_bits(domain_size: usize, draws: usize) -> u32 {
+    (FieldElement::MODULUS / (draws as u64 * domain_size as u64)).ilog2()
+}
+
+// Queries for one bit more than SECURITY_BITS, so they and the challenge
+// draws together stay within it. The schedule's product divides the domain
+// size, so the blowup b is an integer, and a codeword far from the code
+// passes a query with probability at most sqrt(1/b) (the Johnson bound): q
+// queries suffice once b^q >= 2^(2·(SECURITY_BITS + 1)).
+pub fn query_count(domain_size: usize, fold_factors: &[usize]) -> usize {
+    let blowup = (domain_size / fold_factors.iter().product::<usize>()) as u128;
+    assert!(blowup >= 2, "FRI needs a blowup of at least 2");
+    let target = 1u128 << (2 * (SECURITY_BITS + 1));
+    let mut reached = 1u128;
+    let mut queries = 0;
+    while reached < target {
+        reached *= blowup;
+        queries += 1;
+    }
+    queries
+}
+
+// SHA-256 invocations `verify` makes: the transcript (label, a root and a
+// challenge per layer, the final value, one draw per query) and, per query,
+// the first-layer fiber in the caller's tree (a leaf and one hash per level
+// for each value) plus the fiber leaf and path on every folded layer
+pub fn estimated_verify_hashes(domain_size: usize, fold_factors: &[usize]) -> usize {
+    let depth = |size: usize| size.next_power_of_two().trailing_zeros() as usize;
+    let queries = query_count(domain_size, fold_factors);
+    let transcript = 2 + 2 * fold_factors.len() + queries;
+    let mut per_query = fold_factors
+        .first()
+        .map_or(0, |&arity| arity * (1 + depth(domain_size)));
+    let mut layer_size = domain_size;
+    for &arity in fold_factors.iter().skip(1) {
+        layer_size /= arity;
+        per_query += 1 + depth(layer_size / arity);
+    }
+    transcript + queries * per_query
+}
+
+// The multiplicative subgroup {g^0, g^1, ..., g^(size-1)}
+pub fn domain(size: usize) -> Vec<FieldElement> {
+    coset(FieldElement::one(), size)
+}
+
+// The coset {s·g^0, s·g^1, ..., s·g^(size-1)} of that subgroup
+pub fn coset(shift: FieldElement, size: usize) -> Vec<FieldElement> {
+    let generator = subgroup_generator(size);
+    let mut x = shift;
+    (0..size)
+        .map(|_| {
+            let point = x;
+            x = x * generator;
+            point
+        })
+        .collect()
+}
+
+pub fn subgroup_generator(size: usize) -> FieldElement {
+    let order = FieldElement::MODULUS - 1;
+    assert!(
+        size > 0 && order.is_multiple_of(size as u64),
+        "Subgroup size must divide p - 1"
+    );
+    FieldElement::new(GENERATOR).pow(order / size as u64)
+}
+
+// Position `t` of the next layer is the image of the fiber
+// {t, t + n, t + 2n, ...} of the current layer, n being the next layer's size.
+// Its points are x·ζ^k for x = s·g^t and ζ a primitive `arity`-th root of unity.
+fn fiber_points(
+    layer_size: usize,
+    arity: usize,
+    shift: FieldElement,
+    t: usize,
+) -> Vec<FieldElement> {
+    let generator = subgroup_generator(layer_size);
+    let step = generator.pow((layer_size / arity) as u64);
+    let mut x = shift * generator.pow(t as u64);
+    (0..arity)
+        .map(|_| {
+            let point = x;
+            x = x * step;
+            point
+        })
+        .collect()
+}
+
+// Positions of the fiber of `t` in a layer folding to `next_size` points
+fn fiber_indices(next_size: usize, arity: usize, t: usize) -> Vec<usize> {
+    (0..arity).map(|k| t + k * next_size).collect()
+}
+
+fn fiber_values(layer: &[FieldElement], arity: usize, t: usize) -> Vec<FieldElement> {
+    let next_size = layer.len() / arity;
+    (0..arity).map(|k| layer[t + k * next_size]).collect()
+}
+
+fn fiber_leaf(values: &[FieldElement]) -> Vec<u8> {
+    let mut leaf = Vec::with_capacity(values.len() * 8);
+    write_fiber_leaf(&mut leaf, values);
+    leaf
+}
+
+// Overwrites `leaf` with the fiber's encoding, so the verifier can reuse one buffer
+fn write_fiber_leaf(leaf: &mut Vec<u8>, values: &[FieldElement]) {
+    leaf.clear();
+    leaf.extend(values.iter().flat_map(|v| v.value().to_le_bytes()));
+}
+
+// Commit phase: `codeword`, the evaluations over the coset `shift`·⟨g⟩ that
+// the caller has committed to and absorbed, is folded with a challenge β;
+// every folded layer is committed fiber by fiber and folded again. Folding
+// f(x) = Σ_j x^j f_j(x^r) gives f'(y) = Σ_j β^j f_j(y), which is the
+// polynomial through the fiber of y evaluated at β. Query phase: positions
+// drawn from the transcript are opened on every folded layer. Returns the
+// codeword positions of each query's first-layer fiber, which the caller
+// opens in its own commitment.
+pub fn prove(
+    transcript: &mut Transcript,
+    codeword: Vec<FieldElement>,
+    shift: FieldElement,
+    fold_factors: &[usize],
+) -> (FriProof, Vec<Vec<usize>>) {
+    assert!(!fold_factors.is_empty(), "FRI needs at least one fold");
+    assert!(
+        codeword
+            .len()
+            .is_multiple_of(fold_factors.iter().product::<usize>()),
+        "Folding schedule must divide the codeword length"
+    );
+
+    let size = codeword.len();
+    let mut layers = Vec::with_capacity(fold_factors.len());
+    let mut layer = codeword;
+    let mut shift = shift;
+    for (i, &arity) in fold_factors.iter().enumerate() {
+        let next_size = layer.len() / arity;
+        let tree = (i > 0).then(|| {
+            let leaves = (0..layer.len() / arity)
+                .map(|t| fiber_leaf(&fiber_values(&layer, arity, t)))
+                .collect();
+            let tree = MerkleTree::new(leaves);
+            transcript.absorb(tree.root());
+            tree
+        });
+        let beta: FieldElement = transcript.challenge_field();
+
+        let folded = (0..next_size)
+            .map(|t| {
+                let xs = fiber_points(layer.len(), arity, shift, t);
+                lagrange_interpolate(&xs, &fiber_values(&layer, arity, t), beta)
+            })
+            .collect();
+        layers.push((layer, tree));
+        layer = folded;
+        shift = shift.pow(arity as u64);
+    }
+
+    let final_value = layer[0];
+    transcript.absorb(&final_value.value().to_le_bytes());
+
+    let count = query_count(size, fold_factors);
+    let mut first_fibers = Vec::with_capacity(count);
+    let queries = (0..count)
+        .map(|_| {
+            let mut position = transcript.challenge_index(size);
+            let mut openings = Vec::with_capacity(layers.len() - 1);
+            for ((values, tree), &arity) in layers.iter().zip(fold_factors) {
+                let next_size = values.len() / arity;
+                let t = position % next_size;
+                match tree {
+                    None => first_fibers.push(fiber_indices(next_size, arity, t)),
+                    Some(tree) => openings.push(FriOpening {
+                        values: fiber_values(values, arity, t),
+                        path: tree.generate_proof(t),
+                    }),
+                }
+                position = t;
+            }
+            openings
+        })
+        .collect();
+
+    let proof = FriProof {
+        fold_factors: fold_factors.to_vec(),
+        layer_roots: layers
+            .iter()
+            .filter_map(|(_, tree)| tree.as_ref().map(MerkleTree::full_root))
+            .collect(),
+        final_value,
+        queries,
+    };
+    (proof, first_fibers)
+}
+
+// Replays the transcript and checks every query against the coset
+// `shift`·⟨g⟩ of `domain_size` points and the verifier's own folding
+// schedule. The first-layer fiber comes from `first_layer`, called with
+// the query number and the fiber's codeword positions and points; it
+// returns the committed values there, or `None` if it can't vouch for them.
+// Each opened fiber of a folded layer must be in that layer's tree and hold
+// the value folded from the layer before, and the last fold must land on
+// the final constant.
+pub fn verify(
+    transcript: &mut Transcript,
+    proof: &FriProof,
+    shift: FieldElement,
+    domain_size: usize,
+    fold_factors: &[usize],
+    mut first_layer: impl FnMut(usize, &[usize], &[FieldElement]) -> Option<Vec<FieldElement>>,
+) -> bool {
+    let layers = fold_factors.len();
+    if layers == 0
+        || proof.fold_factors != fold_factors
+        || proof.layer_roots.len() != layers - 1
+        || order_factors(domain_size).is_none()
+        || !domain_size.is_multiple_of(fold_factors.iter().product::<usize>())
+        || proof.queries.len() != query_count(domain_size, fold_factors)
+    {
+        return false;
+    }
+
+    let mut betas = Vec::with_capacity(layers);
+    betas.push(transcript.challenge_field());
+    for root in &proof.layer_roots {
+        transcript.absorb(root);
+        betas.push(transcript.challenge_field());
+    }
+    transcript.absorb(&proof.final_value.value().to_le_bytes());
+
+    let mut leaf = scratch(Vec::new());
+    for (query_index, query) in proof.queries.iter().enumerate() {
+        if query.len() != layers - 1 {
+            return false;
+        }
+
+        let mut position = transcript.challenge_index(domain_size);
+        let mut layer_size = domain_size;
+        let mut layer_shift = shift;
+        let mut expected = None;
+        for (i, &arity) in fold_factors.iter().enumerate() {
+            let next_size = layer_size / arity;
+            let (t, slot) = (position % next_size, position / next_size);
+            let xs = fiber_points(layer_size, arity, layer_shift, t);
+
+            let folded = if i == 0 {
+                let indices = fiber_indices(next_size, arity, t);
+                match first_layer(query_index, &indices, &xs) {
+                    Some(values) if values.len() == arity => {
+                        lagrange_interpolate(&xs, &values, betas[0])
+                    }
+                    _ => return false,
+                }
+            } else {
+                let opening = &query[i - 1];
+                if opening.values.len() != arity || Some(opening.values[slot]) != expected {
+                    return false;
+                }
+                write_fiber_leaf(&mut leaf, &opening.values);
+                if !MerkleTree::verify_proof(&proof.layer_roots[i - 1], &leaf, &opening.path, t) {
+                    return false;
+                }
+                lagrange_interpolate(&xs, &opening.values, betas[i])
+            };
+
+            expected = Some(folded);
+            position = t;
+            layer_size = next_size;
+            layer_shift = layer_shift.pow(arity as u64);
+        }
+
+        if expected != Some(proof.final_value) {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn evaluate(coeffs: &[FieldElement], x: FieldElement) -> FieldElement {
+        coeffs
+            .iter()
+            .rev()
+            .fold(FieldElement::zero(), |acc, &c| acc * x + c)
+    }
+
+    fn random_elements(rng: &mut StdRng, count: usize) -> Vec<FieldElement> {
+        (0..count)
+            .map(|_| FieldElement::new(rng.gen::<u64>()))
+            .collect()
+    }
+
+    fn codeword(coeffs: &[FieldElement], shift: FieldElement, size: usize) -> Vec<FieldElement> {
+        coset(shift, size)
+            .into_iter()
+            .map(|x| evaluate(coeffs, x))
+            .collect()
+    }
+
+    // Verifies against a codeword the test trusts, checking that the fiber
+    // positions asked for carry the points FRI folds over
+    fn verify_codeword(
+        label: &[u8],
+        proof: &FriProof,
+        shift: FieldElement,
+        codeword: &[FieldElement],
+        schedule: &[usize],
+    ) -> bool {
+        let points = coset(shift, codeword.len());
+        verify(
+            &mut Transcript::new(label),
+            proof,
+            shift,
+            codeword.len(),
+            schedule,
+            |_, indices, xs| {
+                for (&index, &x) in indices.iter().zip(xs) {
+                    assert_eq!(points[index], x);
+                }
+                Some(indices.iter().map(|&index| codeword[index]).collect())
+            },
+        )
+    }
+
+    #[test]
+    fn test_fold_schedule_covers_degree() {
+        assert_eq!(fold_schedule(462, 64), Some(vec![2, 3, 11]));
+        assert_eq!(fold_schedule(462, 1), Some(vec![2]));
+        assert_eq!(fold_schedule(14, 4), Some(vec![7]));
+        assert_eq!(fold_schedule(18, 6), Some(vec![2, 3]));
+        assert_eq!(fold_schedule(18, 7), Some(vec![3, 3]));
+        // No blowup left, or no subgroup of that size
+        assert_eq!(fold_schedule(42, 42), None);
+        assert_eq!(fold_schedule(7, 2), None);
+        assert_eq!(fold_schedule(256, 4), None);
+
+        // Blowup 7 needs 14 queries for 7^q >= 2^38, blowup 2 needs 38
+        assert_eq!(query_count(462, &[2, 3, 11]), 14);
+        assert_eq!(query_count(18, &[3, 3]), 38);
+        // The default proof's five draws leave the queries their share
+        assert_eq!(challenge_soundness_bits(462, 5), SECURITY_BITS + 1);
+        assert!(challenge_soundness_bits(462, 15) < SECURITY_BITS + 1);
+
+        let points = domain(186);
+        assert_eq!(points[0], FieldElement::one());
+        assert_eq!(points[185] * points[1], FieldElement::one());
+    }
+
+    #[test]
+    fn test_low_degree_codeword_verifies() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for (size, coefficients) in [(14, 1), (42, 2), (66, 5), (198, 17), (462, 64)] {
+            let coeffs = random_elements(&mut rng, coefficients);
+            let schedule = fold_schedule(size, coefficients).unwrap();
+            for shift in [FieldElement::one(), FieldElement::new(7)] {
+                let codeword = codeword(&coeffs, shift, size);
+                let (proof, fibers) = prove(
+                    &mut Transcript::new(b"test"),
+                    codeword.clone(),
+                    shift,
+                    &schedule,
+                );
+                assert_eq!(fibers.len(), query_count(size, &schedule));
+                assert!(fibers.iter().all(|fiber| fiber.len() == schedule[0]));
+                assert!(verify_codeword(
+                    b"test", &proof, shift, &codeword, &schedule
+                ));
+
+                // Another transcript means other challenges, which fold a
+                // non-constant codeword to other values
+                if coefficients > 1 {
+                    assert!(!verify_codeword(
+                        b"other", &proof, shift, &codeword, &schedule
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_codeword_fails() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let schedule = fold_schedule(66, 6).unwrap();
+        let one = FieldElement::one();
+        for _ in 0..20 {
+            let codeword = random_elements(&mut rng, 66);
+            let (proof, _) = prove(
+                &mut Transcript::new(b"test"),
+                codeword.clone(),
+                one,
+                &schedule,
+            );
+            assert!(!verify_codeword(b"test", &proof, one, &codeword, &schedule));
+        }
+
+        // A proof for a low-degree codeword says nothing about another codeword
+        let coeffs = random_elements(&mut rng, 6);
+        let honest = codeword(&coeffs, one, 66);
+        let (proof, _) = prove(
+            &mut Transcript::new(b"test"),
+            honest.clone(),
+            one,
+            &schedule,
+        );
+        let mut other = honest;
+        for value in other.iter_mut().take(33) {
+            *value = *value + one;
+        }
+        assert!(!verify_codeword(b"test", &proof, one, &other, &schedule));
+    }
+
+    #[test]
+    fn test_tampered_proof_fails() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let coeffs = random_elements(&mut rng, 9);
+        let one = FieldElement::one();
+        let schedule = fold_schedule(198, 9).unwrap();
+        let codeword = codeword(&coeffs, one, 198);
+        let (proof, _) = prove(
+            &mut Transcript::new(b"test"),
+            codeword.clone(),
+            one,
+            &schedule,
+        );
+        assert!(verify_codeword(b"test", &proof, one, &codeword, &schedule));
+
+        let mut bad = proof.clone();
+        bad.final_value = bad.final_value + one;
+        assert!(!verify_codeword(b"test", &bad, one, &codeword, &schedule));
+
+        let mut bad = proof.clone();
+        bad.queries[3][0].values[0] = bad.queries[3][0].values[0] + one;
+        assert!(!verify_codeword(b"test", &bad, one, &codeword, &schedule));
+
+        let mut bad = proof.clone();
+        bad.layer_roots[0][0] ^= 1;
+        assert!(!verify_codeword(b"test", &bad, one, &codeword, &schedule));
+
+        let mut bad = proof.clone();
+        bad.queries.pop();
+        assert!(!verify_codeword(b"test", &bad, one, &codeword, &schedule));
+
+        // The verifier folds by its own schedule, not the proof's
+        assert!(!verify_codeword(b"test", &proof, one, &codeword, &[2, 11]));
+        let mut bad = proof;
+        bad.fold_factors = vec![5, 0];
+        assert!(!verify_codeword(b"test", &bad, one, &codeword, &schedule));
+    }
+}
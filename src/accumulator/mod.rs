@@ -1,15 +1,17 @@
 // src/accumulator/mod.rs
 
+pub mod fri;
 pub mod reed_solomon;
 
-use crate::crypto::field::FieldElement;
-
 pub trait Accumulator {
     type Proof;
     type State;
+    type FoldProof;
 
     fn new() -> Self;
     fn accumulate(&mut self, state: Self::State) -> Self::Proof;
     fn verify(&self, proof: &Self::Proof) -> bool;
-    fn fold(&mut self, other: &Self) -> Self::Proof;
+    fn fold(&mut self, other: &Self) -> Self::FoldProof;
+    // Serialized length of a proof in bytes
+    fn proof_size(proof: &Self::Proof) -> usize;
 }
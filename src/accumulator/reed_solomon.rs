@@ -1,349 +1,3011 @@
+use super::fri::{self, FriProof};
 use super::Accumulator;
-use crate::crypto::{field::FieldElement, merkle::MerkleTree};
-use std::fmt::Write;
+use crate::crypto::{
+    field::{FieldElement, FieldError},
+    merkle::MerkleTree,
+    poly::{try_lagrange_interpolate, Polynomial},
+    transcript::Transcript,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use subtle::Choice;
 
-const EVAL_DOMAIN_SIZE: usize = 256;
+// 2·3·7·11, a subgroup order FRI can fold
+const EVAL_DOMAIN_SIZE: usize = 462;
+const DEFAULT_DEGREE_BOUND: usize = 64; // FRI blowup 7 over the default domain
 const NUM_CHALLENGES: usize = 2;
+// Longest vector `RSProof::from_bytes` accepts, far above any honest proof
+pub const DEFAULT_MAX_ELEMENTS: usize = 1 << 16;
 
-// Helper for debug hex printing
-fn hex_str(bytes: &[u8]) -> String {
-    let mut s = String::with_capacity(2 * bytes.len());
-    for b in bytes {
-        write!(s, "{:02x}", b).unwrap();
-    }
-    s
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RSError {
+    #[error("cannot accumulate an empty state")]
+    EmptyState,
+    #[error("state of {degree} elements exceeds the degree bound {bound}")]
+    DegreeTooLarge { degree: usize, bound: usize },
+    #[error("{known} distinct evaluations known but {needed} are needed to decode")]
+    TooFewPoints { known: usize, needed: usize },
+    #[error("evaluation index {index} is outside a domain of {domain_size} points")]
+    IndexOutOfRange { index: usize, domain_size: usize },
+    #[error("domain point {value} appears more than once")]
+    DuplicateDomainPoint { value: u64 },
+    #[error("evaluation domain is empty")]
+    EmptyDomain,
+    #[error("degree bound must be non-zero")]
+    ZeroDegreeBound,
+    #[error("degree bound {bound} exceeds the domain size {domain_size}")]
+    DegreeBoundTooLarge { bound: usize, domain_size: usize },
+    #[error("need at least one challenge")]
+    ZeroChallenges,
+    #[error("streamed state has more than {bound} elements")]
+    StreamTooLong { bound: usize },
+    #[error("accumulator holds a commitment but no codeword")]
+    NoState,
+    #[error("cannot grow a domain of {size} points to {new_size} points")]
+    InvalidGrowth { size: usize, new_size: usize },
+    #[error("domain is not a coset of a multiplicative subgroup in generator order")]
+    NotACoset,
+    #[error("no FRI folding schedule proves degree bound {bound} over {domain_size} points")]
+    NoFoldSchedule { bound: usize, domain_size: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VerifyError {
+    #[error("proof fields have inconsistent lengths")]
+    Malformed,
+    #[error("challenge points do not match the commitment")]
+    ChallengeMismatch,
+    #[error("opening {index} is not in the committed tree")]
+    InvalidOpening { index: usize },
+    #[error("low-degree proof failed")]
+    LowDegree,
+    #[error("low-degree proof covers more than the degree bound")]
+    DegreeOutOfRange,
+    #[error("proof was made for a different domain size or challenge count")]
+    ParamMismatch,
+    #[error("challenge evaluations do not match the state")]
+    EvaluationMismatch,
+    #[error("proof is not for the expected root")]
+    RootMismatch,
+    #[error("proof does not open domain position {index}")]
+    NotOpened { index: usize },
+    #[error("opened value at domain position {index} is not the claimed one")]
+    ValueMismatch { index: usize },
+    #[error("accumulator domain repeats a point")]
+    DegenerateDomain,
+    #[error("opened positions are not the ones drawn from the commitment")]
+    IndexMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProofError {
+    #[error("serialized proof ends unexpectedly")]
+    Truncated,
+    #[error("declared length {declared} exceeds the limit of {limit}")]
+    TooLarge { declared: usize, limit: usize },
+    #[error("value {0} is not a reduced field element")]
+    NonCanonical(u64),
+    #[error("length {actual} where {expected} was expected")]
+    UnexpectedLength { expected: usize, actual: usize },
+    #[error("{0} bytes left over after the proof")]
+    TrailingBytes(usize),
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReedSolomonAccumulator {
     evaluations: Vec<FieldElement>,
     domain: Vec<FieldElement>,
     degree: usize,
-    merkle_root: Vec<u8>,
+    degree_bound: usize,
+    num_challenges: usize,
+    merkle_root: [u8; 32],
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RSProof {
     challenge_evals: Vec<FieldElement>,
     challenge_points: Vec<FieldElement>,
     domain_evals: Vec<FieldElement>,
     eval_indices: Vec<usize>,
-    merkle_root: Vec<u8>,
+    merkle_root: [u8; 32],
     merkle_proofs: Vec<Vec<Vec<u8>>>,
+    // Prover configuration, so a differently configured verifier fails cleanly
+    domain_size: usize,
+    num_challenges: usize,
+    degree_bound: usize,
+    fri: FriProof,
+    // Per FRI query, the codeword values and Merkle paths on its first-layer
+    // fiber, so the verifier knows FRI folded the committed codeword
+    fri_openings: Vec<ValuesAndPaths>,
 }
 
-impl ReedSolomonAccumulator {
-    // Evaluation functions remain unchanged...
-    fn evaluate_at(&self, x: FieldElement) -> FieldElement {
-        if self.degree == 0 {
-            return FieldElement::zero();
-        }
+// Result of `fold`: the proof for the combined state together with the two
+// commitments that went into it and the challenge they were combined with,
+// so an auditor can check which states were folded
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FoldProof {
+    pub combined: RSProof,
+    pub left_root: [u8; 32],
+    pub right_root: [u8; 32],
+    pub alpha: FieldElement,
+}
 
-        for i in 0..self.degree {
-            if x == self.domain[i] {
-                return self.evaluations[i];
-            }
-        }
+// One proof that several commitments over the same domain hold low-degree
+// codewords with the claimed values at their challenge points, i.e. what
+// an `RSProof` shows for each of them. Every commitment's DEEP quotient is
+// combined with Fiat-Shamir coefficients and a single FRI runs on the
+// combination: a commitment far from the code, or with a wrong claimed
+// value, makes the combination far from low-degree. The first FRI layer is
+// computed from the committed codewords, so each query opens one fiber
+// per commitment. Each commitment adds a combination coefficient to the
+// challenge draws, so n commitments over the default domain get
+// `fri::challenge_soundness_bits(462, n + 5)` bits from the draws, fewer
+// than a single proof's.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregateProof {
+    roots: Vec<[u8; 32]>,
+    // Per root, the committed polynomial at the root's challenge points
+    challenge_evals: Vec<Vec<FieldElement>>,
+    fri: FriProof,
+    // Per FRI query, per root, the values and Merkle paths on the fiber
+    fri_openings: Vec<Vec<ValuesAndPaths>>,
+}
 
-        let mut num = FieldElement::zero();
-        let mut den = FieldElement::zero();
+type MerklePath = Vec<Vec<u8>>;
+// Codeword values with their Merkle paths, one per value
+type ValuesAndPaths = (Vec<FieldElement>, Vec<MerklePath>);
 
-        for i in 0..self.degree {
-            let mut weight = FieldElement::one();
-            for j in 0..self.degree {
-                if i != j {
-                    weight = weight * (x - self.domain[j]) / (self.domain[i] - self.domain[j]);
-                }
-            }
-            num = num + weight * self.evaluations[i];
-            den = den + weight;
-        }
+// Codeword values at caller-chosen positions with their Merkle paths. It
+// only shows the values are in the commitment; nothing ties the positions to
+// the root, so it is not an `RSProof` and `verify` doesn't accept it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Opening {
+    merkle_root: [u8; 32],
+    indices: Vec<usize>,
+    values: Vec<FieldElement>,
+    paths: Vec<MerklePath>,
+}
 
-        if den.value() == 0 {
-            return FieldElement::zero();
-        }
-        num / den
+impl Opening {
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.merkle_root
     }
 
-    fn serialize_field_element(fe: &FieldElement) -> Vec<u8> {
-        let value = fe.value();
-        let mut result = vec![0u8; 8];
-        result.copy_from_slice(&value.to_le_bytes());
-        result
+    // Opened positions paired with their values, in opening order
+    pub fn openings(&self) -> Vec<(usize, FieldElement)> {
+        self.indices
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+            .collect()
     }
 
-    fn build_merkle_tree(&self) -> (MerkleTree, Vec<Vec<u8>>) {
-        println!("\nBuilding Merkle tree:");
-        let leaves: Vec<Vec<u8>> = self.evaluations[..self.degree]
+    // Checks every value against `root`, serializing all leaves through one
+    // buffer. `InvalidOpening` gives the position in opening order.
+    pub fn verify(&self, root: &[u8; 32]) -> crate::error::Result<()> {
+        if self.values.len() != self.indices.len() || self.paths.len() != self.indices.len() {
+            return Err(VerifyError::Malformed.into());
+        }
+        let mut leaf = scratch(Vec::with_capacity(8));
+        for (i, ((&index, value), path)) in self
+            .indices
             .iter()
-            .map(|eval| {
-                let leaf = Self::serialize_field_element(eval);
-                println!("Leaf for eval {}: {}", eval.value(), hex_str(&leaf));
-                leaf
-            })
-            .collect();
-
-        println!("Total leaves: {}", leaves.len());
+            .zip(&self.values)
+            .zip(&self.paths)
+            .enumerate()
+        {
+            if !verify_leaf(root, value, path, index, &mut leaf) {
+                return Err(VerifyError::InvalidOpening { index: i }.into());
+            }
+        }
+        Ok(())
+    }
+}
 
-        let tree = MerkleTree::new(leaves.clone());
-        println!("Tree root: {}", hex_str(&tree.root()));
+// Whether `value` sits at `index` under `root`, serializing it into `leaf`
+fn verify_leaf(
+    root: &[u8; 32],
+    value: &FieldElement,
+    path: &[Vec<u8>],
+    index: usize,
+    leaf: &mut Vec<u8>,
+) -> bool {
+    leaf.clear();
+    leaf.extend_from_slice(&value.value().to_le_bytes());
+    MerkleTree::verify_proof(root, leaf, path, index)
+}
 
-        (tree, leaves)
+// The DEEP quotient Σ_j γ^j (y - v_j) / (x - z_j) at a domain point x
+// where the committed codeword is y. Fails only for x equal to some z_j.
+fn deep_quotient(
+    x: FieldElement,
+    y: FieldElement,
+    points: &[FieldElement],
+    evals: &[FieldElement],
+    gamma: FieldElement,
+) -> Result<FieldElement, FieldError> {
+    let mut power = FieldElement::one();
+    let mut sum = FieldElement::zero();
+    for (&z, &v) in points.iter().zip(evals) {
+        sum = sum + power * (y - v).try_div(x - z)?;
+        power = power * gamma;
     }
+    Ok(sum)
+}
 
-    fn verify_merkle_proof(
-        &self,
-        root: &[u8],
-        proof: &[Vec<u8>],
-        leaf: &[u8],
-        index: usize,
-    ) -> bool {
-        println!("\nVerifying Merkle proof:");
-        println!("Root: {}", hex_str(root));
-        println!("Leaf: {}", hex_str(leaf));
-        println!("Index: {}", index);
-        println!("Proof length: {}", proof.len());
+// FRI at `schedule` proves degree below the product of its arities. An
+// honest DEEP quotient has degree below `degree_bound - 1`; multiplying it
+// by 1 + c·x^k for this k and a random c lifts that bound to exactly the
+// product, so a quotient of higher degree fails FRI.
+fn degree_correction(schedule: &[usize], degree_bound: usize) -> u64 {
+    (schedule.iter().product::<usize>() + 1 - degree_bound) as u64
+}
 
-        for (i, p) in proof.iter().enumerate() {
-            println!("Proof element {}: {}", i, hex_str(p));
-        }
+// Whether `domain` is s·g^0, s·g^1, ... for g generating the subgroup of
+// its size, which must divide p - 1
+fn is_coset(domain: &[FieldElement]) -> bool {
+    if !(FieldElement::MODULUS - 1).is_multiple_of(domain.len() as u64) {
+        return false;
+    }
+    let generator = fri::subgroup_generator(domain.len());
+    domain.windows(2).all(|pair| pair[1] == pair[0] * generator)
+}
 
-        let result = MerkleTree::verify_proof(root, leaf, proof, index);
-        println!("Verification result: {}", result);
-        result
+impl AggregateProof {
+    // Commitments covered by the proof, in aggregation order
+    pub fn roots(&self) -> &[[u8; 32]] {
+        &self.roots
     }
 }
 
-impl Accumulator for ReedSolomonAccumulator {
-    type Proof = RSProof;
-    type State = Vec<FieldElement>;
+// Scratch space for serialized field elements. With `zeroize` it is wiped
+// when dropped, so committed values don't linger in freed memory.
+#[cfg(feature = "zeroize")]
+pub(super) type Scratch<T> = zeroize::Zeroizing<T>;
+#[cfg(not(feature = "zeroize"))]
+pub(super) type Scratch<T> = T;
 
-    fn new() -> Self {
-        let domain: Vec<FieldElement> = (0..EVAL_DOMAIN_SIZE)
-            .map(|i| FieldElement::from(i as u64))
-            .collect();
+#[cfg(feature = "zeroize")]
+pub(super) fn scratch<T: zeroize::Zeroize>(value: T) -> Scratch<T> {
+    zeroize::Zeroizing::new(value)
+}
 
-        let evaluations = vec![FieldElement::zero(); EVAL_DOMAIN_SIZE];
-        let tree = MerkleTree::new(vec![]);
+#[cfg(not(feature = "zeroize"))]
+pub(super) fn scratch<T>(value: T) -> Scratch<T> {
+    value
+}
 
-        ReedSolomonAccumulator {
-            evaluations,
-            domain,
-            degree: 0,
-            merkle_root: tree.root(),
-        }
+// Length prefixes in the proof encoding are little-endian u32
+pub(super) fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+pub(super) fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn write_state(elements: &[FieldElement]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + 8 * elements.len());
+    write_elements(&mut out, elements);
+    out
+}
+
+pub(super) fn write_elements(out: &mut Vec<u8>, elements: &[FieldElement]) {
+    write_len(out, elements.len());
+    for fe in elements {
+        let bytes = scratch(ReedSolomonAccumulator::serialize_field_element(fe));
+        out.extend_from_slice(&bytes);
     }
+}
 
-    fn accumulate(&mut self, state: Self::State) -> Self::Proof {
-        println!("\nAccumulating state of size: {}", state.len());
+// Decoder for the `to_bytes` encodings. Every length prefix is checked
+// against `max_elements` before anything is allocated for it.
+pub(super) struct ProofReader<'a> {
+    bytes: &'a [u8],
+    max_elements: usize,
+}
 
-        self.evaluations.clear();
-        self.evaluations.extend(state.iter());
-        self.degree = state.len();
+impl<'a> ProofReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ProofError> {
+        if self.bytes.len() < count {
+            return Err(ProofError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(head)
+    }
 
-        let (tree, leaves) = self.build_merkle_tree();
-        self.merkle_root = tree.root();
+    pub(super) fn read_u64(&mut self) -> Result<u64, ProofError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
 
-        let eval_indices: Vec<usize> = (0..NUM_CHALLENGES).map(|i| i % self.degree).collect();
+    pub(super) fn read_len(&mut self) -> Result<usize, ProofError> {
+        let declared = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        if declared > self.max_elements {
+            return Err(ProofError::TooLarge {
+                declared,
+                limit: self.max_elements,
+            });
+        }
+        Ok(declared)
+    }
 
-        println!("Selected indices for proofs: {:?}", eval_indices);
+    pub(super) fn read_elements(&mut self) -> Result<Vec<FieldElement>, ProofError> {
+        let len = self.read_len()?;
+        let mut elements = Vec::with_capacity(len);
+        for _ in 0..len {
+            let value = self.read_u64()?;
+            if value >= FieldElement::MODULUS {
+                return Err(ProofError::NonCanonical(value));
+            }
+            elements.push(FieldElement::new(value));
+        }
+        Ok(elements)
+    }
 
-        let domain_evals: Vec<FieldElement> = eval_indices
-            .iter()
-            .map(|&idx| self.evaluations[idx])
-            .collect();
+    // A length-prefixed hash, which must be 32 bytes
+    pub(super) fn read_node(&mut self) -> Result<[u8; 32], ProofError> {
+        let len = self.read_len()?;
+        let node = self.take(len)?;
+        node.try_into().map_err(|_| ProofError::UnexpectedLength {
+            expected: 32,
+            actual: len,
+        })
+    }
 
-        let merkle_proofs: Vec<Vec<Vec<u8>>> = eval_indices
-            .iter()
-            .map(|&idx| {
-                let proof = tree.generate_proof(idx);
-                println!("Generated proof for index {}", idx);
-                proof
-            })
-            .collect();
+    pub(super) fn read_path(&mut self) -> Result<Vec<Vec<u8>>, ProofError> {
+        let len = self.read_len()?;
+        let mut path = Vec::with_capacity(len);
+        for _ in 0..len {
+            path.push(self.read_node()?.to_vec());
+        }
+        Ok(path)
+    }
+}
 
-        let challenge_points: Vec<FieldElement> = (0..NUM_CHALLENGES)
-            .map(|_| loop {
-                let point = FieldElement::random();
-                if !self.domain[..self.degree].contains(&point) {
-                    return point;
-                }
-            })
-            .collect();
+impl RSProof {
+    // Inverse of `to_bytes`, with every declared length capped at
+    // `DEFAULT_MAX_ELEMENTS`. Only the encoding is checked; the result
+    // still has to be verified.
+    pub fn from_bytes(bytes: &[u8]) -> crate::error::Result<RSProof> {
+        Self::from_bytes_with_limit(bytes, DEFAULT_MAX_ELEMENTS)
+    }
 
-        let challenge_evals: Vec<FieldElement> = challenge_points
-            .iter()
-            .map(|&point| self.evaluate_at(point))
-            .collect();
+    // Like `from_bytes`, rejecting any vector declared longer than `max_elements`
+    pub fn from_bytes_with_limit(
+        bytes: &[u8],
+        max_elements: usize,
+    ) -> crate::error::Result<RSProof> {
+        let mut reader = ProofReader {
+            bytes,
+            max_elements,
+        };
+        let challenge_evals = reader.read_elements()?;
+        let challenge_points = reader.read_elements()?;
+        let domain_evals = reader.read_elements()?;
+        let index_count = reader.read_len()?;
+        let mut eval_indices = Vec::with_capacity(index_count);
+        for _ in 0..index_count {
+            eval_indices.push(reader.read_u64()? as usize);
+        }
+        let merkle_root = reader.read_node()?;
+        let proof_count = reader.read_len()?;
+        let mut merkle_proofs = Vec::with_capacity(proof_count);
+        for _ in 0..proof_count {
+            merkle_proofs.push(reader.read_path()?);
+        }
+        let domain_size = reader.read_u64()? as usize;
+        let num_challenges = reader.read_u64()? as usize;
+        let degree_bound = reader.read_u64()? as usize;
+        let fri = FriProof::read(&mut reader)?;
+        let opening_count = reader.read_len()?;
+        let mut fri_openings = Vec::with_capacity(opening_count);
+        for _ in 0..opening_count {
+            let values = reader.read_elements()?;
+            let path_count = reader.read_len()?;
+            let mut paths = Vec::with_capacity(path_count);
+            for _ in 0..path_count {
+                paths.push(reader.read_path()?);
+            }
+            fri_openings.push((values, paths));
+        }
 
-        RSProof {
+        if !reader.bytes.is_empty() {
+            return Err(ProofError::TrailingBytes(reader.bytes.len()).into());
+        }
+        Ok(RSProof {
             challenge_evals,
             challenge_points,
             domain_evals,
             eval_indices,
-            merkle_root: self.merkle_root.clone(),
+            merkle_root,
             merkle_proofs,
-        }
+            domain_size,
+            num_challenges,
+            degree_bound,
+            fri,
+            fri_openings,
+        })
     }
 
-    fn verify(&self, proof: &Self::Proof) -> bool {
-        println!("\nVerifying proof");
-        println!("Number of merkle proofs: {}", proof.merkle_proofs.len());
-        println!("Number of evaluations: {}", proof.domain_evals.len());
+    // Root of the evaluation commitment the proof opens against
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.merkle_root
+    }
 
-        // Verify Merkle proofs
-        for (i, (&idx, proof_path)) in proof
+    // Number of coefficients the low-degree proof bounds the state to
+    pub fn degree_bound(&self) -> usize {
+        self.degree_bound
+    }
+
+    // Opened domain positions paired with their claimed values, in proof
+    // order. A decoded proof may carry mismatched lists, which is Malformed.
+    pub fn openings(&self) -> crate::error::Result<Vec<(usize, FieldElement)>> {
+        if self.eval_indices.len() != self.domain_evals.len() {
+            return Err(VerifyError::Malformed.into());
+        }
+        Ok(self
             .eval_indices
             .iter()
-            .zip(proof.merkle_proofs.iter())
-            .enumerate()
-        {
-            let eval = proof.domain_evals[i];
-            println!(
-                "\nVerifying proof {} for eval {} at index {}",
-                i,
-                eval.value(),
-                idx
-            );
+            .copied()
+            .zip(self.domain_evals.iter().copied())
+            .collect())
+    }
 
-            let leaf = Self::serialize_field_element(&eval);
-            if !self.verify_merkle_proof(&proof.merkle_root, proof_path, &leaf, idx) {
-                return false;
+    // Canonical encoding: every field in declaration order, each vector
+    // prefixed by its length, field elements as 8-byte little-endian values
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_elements(&mut out, &self.challenge_evals);
+        write_elements(&mut out, &self.challenge_points);
+        write_elements(&mut out, &self.domain_evals);
+        write_len(&mut out, self.eval_indices.len());
+        for &idx in &self.eval_indices {
+            out.extend_from_slice(&(idx as u64).to_le_bytes());
+        }
+        write_bytes(&mut out, &self.merkle_root);
+        write_len(&mut out, self.merkle_proofs.len());
+        for path in &self.merkle_proofs {
+            write_len(&mut out, path.len());
+            for node in path {
+                write_bytes(&mut out, node);
             }
         }
-
-        // Verify polynomial evaluations
-        for (i, &point) in proof.challenge_points.iter().enumerate() {
-            let expected = proof.challenge_evals[i];
-            let computed = self.evaluate_at(point);
-            if expected != computed {
-                return false;
+        out.extend_from_slice(&(self.domain_size as u64).to_le_bytes());
+        out.extend_from_slice(&(self.num_challenges as u64).to_le_bytes());
+        out.extend_from_slice(&(self.degree_bound as u64).to_le_bytes());
+        out.extend_from_slice(&self.fri.to_bytes());
+        write_len(&mut out, self.fri_openings.len());
+        for (values, paths) in &self.fri_openings {
+            write_elements(&mut out, values);
+            write_len(&mut out, paths.len());
+            for path in paths {
+                write_len(&mut out, path.len());
+                for node in path {
+                    write_bytes(&mut out, node);
+                }
             }
         }
+        out
+    }
 
-        true
+    // Length of `to_bytes()`, computed without serializing
+    pub fn size_bytes(&self) -> usize {
+        let elements = |count: usize| 4 + 8 * count;
+        let path = |path: &MerklePath| 4 + path.iter().map(|node| 4 + node.len()).sum::<usize>();
+        let paths: usize = self.merkle_proofs.iter().map(path).sum();
+        let fri_openings: usize = self
+            .fri_openings
+            .iter()
+            .map(|(values, paths)| {
+                elements(values.len()) + 4 + paths.iter().map(path).sum::<usize>()
+            })
+            .sum();
+        elements(self.challenge_evals.len())
+            + elements(self.challenge_points.len())
+            + elements(self.domain_evals.len())
+            + elements(self.eval_indices.len())
+            + 4
+            + self.merkle_root.len()
+            + 4
+            + paths
+            + 24
+            + self.fri.size_bytes()
+            + 4
+            + fri_openings
     }
 
-    fn fold(&mut self, other: &Self) -> Self::Proof {
-        let alpha = FieldElement::random();
-        let max_deg = self.degree.max(other.degree);
+    // Identical proofs share an id, so peers relaying the same proof can be deduplicated
+    pub fn proof_id(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
 
-        println!("\nFolding two accumulators:");
-        println!("First degree: {}", self.degree);
-        println!("Second degree: {}", other.degree);
-        println!("Max degree: {}", max_deg);
+    // Check a single opening against a trusted root so a verifier can process
+    // openings one at a time and stop at the first bad one
+    pub fn verify_opening_at(&self, i: usize, root: &[u8; 32]) -> bool {
+        let mut leaf = scratch(Vec::new());
+        self.verify_opening_with(i, root, &mut leaf)
+    }
 
-        let mut new_evals = Vec::with_capacity(max_deg);
+    // `verify_opening_at` serializing the leaf into `leaf`, so checking
+    // every opening of a proof needs a single buffer
+    fn verify_opening_with(&self, i: usize, root: &[u8; 32], leaf: &mut Vec<u8>) -> bool {
+        let (Some(&index), Some(eval), Some(path)) = (
+            self.eval_indices.get(i),
+            self.domain_evals.get(i),
+            self.merkle_proofs.get(i),
+        ) else {
+            return false;
+        };
 
-        for i in 0..max_deg {
-            let self_eval = if i < self.degree {
-                self.evaluations[i]
-            } else {
-                FieldElement::zero()
-            };
+        verify_leaf(root, eval, path, index, leaf)
+    }
+}
 
-            let other_eval = if i < other.degree {
-                other.evaluations[i]
-            } else {
-                FieldElement::zero()
-            };
+impl ReedSolomonAccumulator {
+    // Accumulator over the multiplicative subgroup of `domain_size` points
+    // (which must divide p - 1) committing to states of at most
+    // `degree_bound` elements
+    pub fn with_params(domain_size: usize, degree_bound: usize, num_challenges: usize) -> Self {
+        Self::from_domain(fri::domain(domain_size), degree_bound, num_challenges)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-            new_evals.push(self_eval + alpha * other_eval);
-        }
+    // Default accumulator that knows a commitment but not the state behind
+    // it, such as one rebuilt from a received block. `verify` then checks
+    // proofs against `root` the way `verify_proof` does, without the
+    // evaluation checks that need the state; committing replaces the root.
+    pub fn from_root(root: [u8; 32]) -> Self {
+        let mut acc = <Self as Accumulator>::new();
+        acc.evaluations.clear();
+        acc.merkle_root = root;
+        acc
+    }
 
-        self.evaluations = new_evals;
-        self.degree = max_deg;
+    // Every constructor but `from_root` fills the evaluations to the domain size
+    fn has_state(&self) -> bool {
+        !self.evaluations.is_empty()
+    }
 
-        self.accumulate(self.evaluations[..self.degree].to_vec())
+    // Accumulator over the multiplicative subgroup {g^0, ..., g^(size-1)},
+    // the domain shape NTT-based evaluation needs. `size` must divide p - 1.
+    // The degree bound keeps the default rate of 1/4.
+    pub fn new_multiplicative(size: usize) -> Self {
+        Self::with_domain(fri::domain(size)).expect("Subgroup elements are distinct")
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Accumulator over a caller-chosen coset, with a degree bound of a
+    // quarter of the domain and the default challenge count
+    pub fn with_domain(domain: Vec<FieldElement>) -> crate::error::Result<Self> {
+        let degree_bound = (domain.len() / 4).max(1);
+        Self::from_domain(domain, degree_bound, NUM_CHALLENGES)
+    }
 
-    #[test]
-    fn test_serialization_consistency() {
-        let fe = FieldElement::new(123);
-        let bytes = ReedSolomonAccumulator::serialize_field_element(&fe);
-        assert_eq!(bytes.len(), 8, "Serialized field element should be 8 bytes");
+    // Accumulator over the domain s·g^0, s·g^1, ..., s·g^(n-1), g generating
+    // the subgroup of order n: FRI folds the committed codeword itself, which
+    // needs that structure. Interpolation divides by the differences of
+    // domain points, so repeated points are rejected here rather than failing
+    // deep inside evaluation, as is every other parameter the accumulator
+    // can't work with.
+    pub fn from_domain(
+        domain: Vec<FieldElement>,
+        degree_bound: usize,
+        num_challenges: usize,
+    ) -> crate::error::Result<Self> {
+        if domain.is_empty() {
+            return Err(RSError::EmptyDomain.into());
+        }
+        let mut sorted = domain.clone();
+        sorted.sort_unstable();
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(RSError::DuplicateDomainPoint {
+                value: pair[0].value(),
+            }
+            .into());
+        }
+        if !is_coset(&domain) {
+            return Err(RSError::NotACoset.into());
+        }
+        if degree_bound == 0 {
+            return Err(RSError::ZeroDegreeBound.into());
+        }
+        if degree_bound > domain.len() {
+            return Err(RSError::DegreeBoundTooLarge {
+                bound: degree_bound,
+                domain_size: domain.len(),
+            }
+            .into());
+        }
+        if num_challenges == 0 {
+            return Err(RSError::ZeroChallenges.into());
+        }
+        if fri::fold_schedule(domain.len(), degree_bound).is_none() {
+            return Err(RSError::NoFoldSchedule {
+                bound: degree_bound,
+                domain_size: domain.len(),
+            }
+            .into());
+        }
 
-        // Verify value can be recovered
-        let mut value_bytes = [0u8; 8];
-        value_bytes.copy_from_slice(&bytes);
-        let value = u64::from_le_bytes(value_bytes);
-        assert_eq!(value, fe.value());
+        let mut acc = ReedSolomonAccumulator {
+            evaluations: Vec::new(),
+            domain,
+            degree: 0,
+            degree_bound,
+            num_challenges,
+            merkle_root: [0u8; 32],
+        };
+        acc.reset();
+        Ok(acc)
     }
 
-    #[test]
-    fn test_merkle_tree_basic() {
-        println!("\nRunning basic Merkle tree test");
+    // Drops the current commitment but keeps the domain and the buffer's
+    // allocation, leaving the accumulator as freshly constructed
+    pub fn reset(&mut self) {
+        self.evaluations.clear();
+        self.evaluations
+            .resize(self.domain.len(), FieldElement::zero());
+        self.degree = 0;
+        self.merkle_root = MerkleTree::new(vec![]).full_root();
+    }
 
-        let leaves: Vec<Vec<u8>> = vec![vec![1, 0, 0, 0, 0, 0, 0, 0], vec![2, 0, 0, 0, 0, 0, 0, 0]];
+    // Extends the domain to the coset of `new_size` points with the same
+    // shift. `new_size` must be a multiple of the current size that divides
+    // p - 1; the old domain is then every (new_size / size)-th point of the
+    // new one. The degree bound grows with it so the rate stays the same. A
+    // committed polynomial is evaluated over the new domain and recommitted,
+    // so it keeps its values at the old points. An accumulator from
+    // `from_root` has no codeword to extend, so it is rejected rather than
+    // given an all-zero one.
+    pub fn grow_domain(&mut self, new_size: usize) -> crate::error::Result<()> {
+        if !self.has_state() {
+            return Err(RSError::NoState.into());
+        }
+        let old_size = self.domain.len();
+        if new_size < old_size
+            || !new_size.is_multiple_of(old_size)
+            || !(FieldElement::MODULUS - 1).is_multiple_of(new_size as u64)
+        {
+            return Err(RSError::InvalidGrowth {
+                size: old_size,
+                new_size,
+            }
+            .into());
+        }
+        // Scaling keeps any schedule `from_domain` accepted, but a
+        // deserialized accumulator may carry a bound that never had one
+        let degree_bound = self.degree_bound * new_size / old_size;
+        if fri::fold_schedule(new_size, degree_bound).is_none() {
+            return Err(RSError::NoFoldSchedule {
+                bound: degree_bound,
+                domain_size: new_size,
+            }
+            .into());
+        }
 
-        println!("Building tree with leaves:");
-        for (i, leaf) in leaves.iter().enumerate() {
-            println!("Leaf {}: {}", i, hex_str(leaf));
+        let poly = (self.degree > 0).then(|| self.polynomial());
+        self.domain = fri::coset(self.domain[0], new_size);
+        self.degree_bound = degree_bound;
+        match poly {
+            None => self.evaluations.resize(new_size, FieldElement::zero()),
+            Some(poly) => {
+                self.evaluations.clear();
+                self.evaluations
+                    .extend(self.domain.iter().map(|&x| poly.eval(x)));
+                self.commit_codeword();
+            }
         }
+        Ok(())
+    }
 
-        let tree = MerkleTree::new(leaves.clone());
-        let root = tree.root();
-        println!("Tree root: {}", hex_str(&root));
+    // Number of state elements the current commitment covers
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
 
-        let proof = tree.generate_proof(0);
-        let verified = MerkleTree::verify_proof(&root, &leaves[0], &proof, 0);
-        assert!(verified, "Basic Merkle proof verification failed");
+    pub fn domain_size(&self) -> usize {
+        self.domain.len()
     }
 
-    #[test]
-    fn test_merkle_proof_verification() {
-        let mut acc = ReedSolomonAccumulator::new();
-        let state = vec![FieldElement::new(1), FieldElement::new(2)];
-        let proof = acc.accumulate(state);
-        assert!(acc.verify(&proof), "Basic Merkle proof verification failed");
+    pub fn domain(&self) -> &[FieldElement] {
+        &self.domain
     }
 
-    #[test]
-    fn test_accumulator_basic() {
-        let mut acc = ReedSolomonAccumulator::new();
-        let state = vec![
-            FieldElement::new(1),
-            FieldElement::new(2),
-            FieldElement::new(3),
-        ];
-        let proof = acc.accumulate(state);
-        assert!(acc.verify(&proof), "Basic test failed");
+    // Root of the current evaluation commitment
+    pub fn merkle_root(&self) -> &[u8] {
+        &self.merkle_root
     }
 
-    #[test]
-    fn test_accumulator_fold() {
-        let mut acc1 = ReedSolomonAccumulator::new();
-        let mut acc2 = ReedSolomonAccumulator::new();
+    // Whether both accumulators hold the same commitment. Only the roots are
+    // compared: equal roots bind to equal codewords (and so equal states over
+    // the same domain), but the evaluations themselves are not inspected.
+    pub fn commitment_eq(&self, other: &ReedSolomonAccumulator) -> bool {
+        self.merkle_root == other.merkle_root
+    }
 
-        let state1 = vec![FieldElement::new(1), FieldElement::new(2)];
-        let state2 = vec![FieldElement::new(3), FieldElement::new(4)];
+    // Like `accumulate`, but reports an empty or over-degree state instead of panicking
+    pub fn try_accumulate(&mut self, state: Vec<FieldElement>) -> crate::error::Result<RSProof> {
+        self.check_degree(state.len())?;
+        self.encode(state);
+        let tree = self.commit_codeword();
+        Ok(self.prove_with(&tree))
+    }
 
-        let proof1 = acc1.accumulate(state1);
-        assert!(
-            acc1.verify(&proof1),
-            "First accumulator verification failed"
-        );
+    // Commits to a polynomial given by its coefficients (lowest degree first)
+    // by evaluating it over the whole domain. `accumulate` instead takes the
+    // polynomial's values on the first domain points. Both produce the same
+    // proof for the same polynomial.
+    pub fn accumulate_coefficients(&mut self, coeffs: &[FieldElement]) -> RSProof {
+        if let Err(err) = self.check_degree(coeffs.len()) {
+            panic!("{}", err);
+        }
 
-        let proof2 = acc2.accumulate(state2);
+        let poly = Polynomial::new(coeffs.to_vec());
+        self.degree = coeffs.len();
+        self.evaluations.clear();
+        self.evaluations
+            .extend(self.domain.iter().map(|&x| poly.eval(x)));
+        let tree = self.commit_codeword();
+        self.prove_with(&tree)
+    }
+
+    // Erasure decoding: any `degree` known evaluations of a codeword of a
+    // polynomial of degree < `degree` determine it, so interpolate through
+    // them and re-evaluate over the whole domain. Repeated indices count once.
+    pub fn reconstruct(
+        domain: &[FieldElement],
+        known: &[(usize, FieldElement)],
+        degree: usize,
+    ) -> crate::error::Result<Vec<FieldElement>> {
+        let mut seen = vec![false; domain.len()];
+        let mut xs = Vec::with_capacity(degree);
+        let mut ys = Vec::with_capacity(degree);
+        for &(index, value) in known {
+            if index >= domain.len() {
+                return Err(RSError::IndexOutOfRange {
+                    index,
+                    domain_size: domain.len(),
+                }
+                .into());
+            }
+            if !std::mem::replace(&mut seen[index], true) && xs.len() < degree {
+                xs.push(domain[index]);
+                ys.push(value);
+            }
+        }
+        if xs.len() < degree {
+            return Err(RSError::TooFewPoints {
+                known: xs.len(),
+                needed: degree,
+            }
+            .into());
+        }
+
+        let poly = Polynomial::interpolate(&xs, &ys);
+        Ok(domain.iter().map(|&x| poly.eval(x)).collect())
+    }
+
+    fn check_degree(&self, len: usize) -> Result<(), RSError> {
+        if len == 0 {
+            return Err(RSError::EmptyState);
+        }
+        if len > self.degree_bound {
+            return Err(RSError::DegreeTooLarge {
+                degree: len,
+                bound: self.degree_bound,
+            });
+        }
+        Ok(())
+    }
+
+    // Commits to `state` without opening anything, e.g. for a block header
+    // written before the proof is needed. `prove` proves later.
+    pub fn commit(&mut self, state: Vec<FieldElement>) -> [u8; 32] {
+        if let Err(err) = self.check_degree(state.len()) {
+            panic!("{}", err);
+        }
+
+        self.encode(state);
+        self.commit_codeword();
+        self.merkle_root
+    }
+
+    // Proof for the current commitment, the same one `accumulate` returns
+    pub fn prove(&self) -> RSProof {
+        assert!(self.degree > 0, "Nothing has been committed");
+        let tree = self.build_merkle_tree();
+        self.prove_with(&tree)
+    }
+
+    // The codeword's values at `indices`, e.g. to serve a request for
+    // particular positions. This is not a proof `verify` accepts: a proof
+    // only opens the positions drawn from the commitment.
+    pub fn open(&self, indices: &[usize]) -> Opening {
+        assert!(self.degree > 0, "Nothing has been committed");
         assert!(
-            acc2.verify(&proof2),
-            "Second accumulator verification failed"
+            indices.iter().all(|&idx| idx < self.evaluations.len()),
+            "Opening index out of range"
         );
+        let tree = self.build_merkle_tree();
+        Opening {
+            merkle_root: self.merkle_root,
+            indices: indices.to_vec(),
+            values: indices.iter().map(|&idx| self.evaluations[idx]).collect(),
+            paths: indices
+                .iter()
+                .map(|&idx| tree.generate_proof(idx))
+                .collect(),
+        }
+    }
 
-        let folded_proof = acc1.fold(&acc2);
-        assert!(acc1.verify(&folded_proof), "Folded verification failed");
+    // Positions a proof opens: one per challenge, drawn from the current
+    // root (Fiat-Shamir) so they can't be predicted before committing, and
+    // spread over the whole domain without repeats
+    pub fn default_indices(&self) -> Vec<usize> {
+        let mut transcript = Self::index_transcript(&self.merkle_root);
+        self.distinct_indices(&mut transcript)
     }
 
-    #[test]
-    fn test_accumulator_large_state() {
-        let mut acc = ReedSolomonAccumulator::new();
-        let state: Vec<FieldElement> = (0..5).map(|i| FieldElement::new(i as u64)).collect();
-        let proof = acc.accumulate(state);
-        assert!(acc.verify(&proof), "Large state verification failed");
+    fn index_transcript(merkle_root: &[u8; 32]) -> Transcript {
+        let mut transcript = Transcript::new(b"endgame-rs-indices");
+        transcript.absorb(merkle_root);
+        transcript
+    }
+
+    // `num_challenges` distinct domain positions (all of them for a smaller domain)
+    fn distinct_indices(&self, transcript: &mut Transcript) -> Vec<usize> {
+        let count = self.num_challenges.min(self.domain.len());
+        let mut indices = Vec::with_capacity(count);
+        while indices.len() < count {
+            let index = Self::next_distinct_index(transcript, self.domain.len(), &indices);
+            indices.push(index);
+        }
+        indices
+    }
+
+    // Next position in `0..domain_size` not among `drawn`
+    fn next_distinct_index(
+        transcript: &mut Transcript,
+        domain_size: usize,
+        drawn: &[usize],
+    ) -> usize {
+        loop {
+            let index = transcript.challenge_index(domain_size);
+            if !drawn.contains(&index) {
+                return index;
+            }
+        }
+    }
+
+    // Merkle-commits the current codeword, returning the tree to open against
+    fn commit_codeword(&mut self) -> MerkleTree {
+        let tree = self.build_merkle_tree();
+        self.merkle_root = tree.full_root();
+        tree
+    }
+
+    // Proof for the codeword committed in `tree`, opened at `default_indices`
+    fn prove_with(&self, tree: &MerkleTree) -> RSProof {
+        let eval_indices = self.default_indices();
+        let domain_evals: Vec<FieldElement> = eval_indices
+            .iter()
+            .map(|&idx| self.evaluations[idx])
+            .collect();
+
+        let merkle_proofs: Vec<Vec<Vec<u8>>> = eval_indices
+            .iter()
+            .map(|&idx| tree.generate_proof(idx))
+            .collect();
+
+        // Challenge points are drawn from the commitment (Fiat-Shamir), so
+        // the same state always yields the same proof
+        let challenge_points = Self::challenge_points(
+            &self.merkle_root,
+            self.num_challenges,
+            self.domain[0],
+            self.domain.len(),
+        );
+
+        let challenge_evals: Vec<FieldElement> = challenge_points
+            .iter()
+            .map(|&point| {
+                self.evaluate_at(point)
+                    .expect("Committed domain points must be distinct")
+            })
+            .collect();
+
+        let (fri, fri_openings) = self.prove_low_degree(tree, &challenge_points, &challenge_evals);
+
+        RSProof {
+            challenge_evals,
+            challenge_points,
+            domain_evals,
+            eval_indices,
+            merkle_root: self.merkle_root,
+            merkle_proofs,
+            domain_size: self.domain.len(),
+            num_challenges: self.num_challenges,
+            degree_bound: self.degree_bound,
+            fri,
+            fri_openings,
+        }
+    }
+
+    // Systematic Reed-Solomon encoding: the state gives the values of a
+    // polynomial of degree < state.len() on the first domain points, and the
+    // codeword is that polynomial evaluated over the whole domain
+    fn encode(&mut self, state: Vec<FieldElement>) {
+        self.degree = state.len();
+        self.evaluations = state;
+        let poly = self.polynomial();
+        self.evaluations.clear();
+        self.evaluations
+            .extend(self.domain.iter().map(|&x| poly.eval(x)));
+    }
+
+    // `try_accumulate` for a state given as an iterator. This is only a
+    // wrapper over the bounded state buffer: items are drawn into a buffer
+    // of at most `degree_bound` elements, so an unbounded iterator is never
+    // collected, but the codeword and tree are still built from the whole
+    // state. A longer stream is rejected after one item past the bound,
+    // leaving the accumulator unchanged. Produces the same proof as
+    // `accumulate` on the collected state.
+    pub fn accumulate_iter<I: IntoIterator<Item = FieldElement>>(
+        &mut self,
+        states: I,
+    ) -> crate::error::Result<RSProof> {
+        let mut state = Vec::with_capacity(self.degree_bound);
+        for element in states {
+            if state.len() == self.degree_bound {
+                return Err(RSError::StreamTooLong {
+                    bound: self.degree_bound,
+                }
+                .into());
+            }
+            state.push(element);
+        }
+        self.try_accumulate(state)
+    }
+
+    // Value at `x` of the committed polynomial
+    // Fails only if the committed domain repeats a point, which `with_domain`
+    // rules out but a deserialized accumulator may not
+    fn evaluate_at(&self, x: FieldElement) -> Result<FieldElement, FieldError> {
+        try_lagrange_interpolate(
+            &self.domain[..self.degree],
+            &self.evaluations[..self.degree],
+            x,
+        )
+    }
+
+    // The committed polynomial in coefficient form
+    fn polynomial(&self) -> Polynomial {
+        Polynomial::interpolate(
+            &self.domain[..self.degree],
+            &self.evaluations[..self.degree],
+        )
+    }
+
+    // Transcript for the low-degree proof, which also binds the claimed
+    // challenge evaluations; returns it with the DEEP combination challenge
+    // and the degree correction challenge
+    fn fri_transcript(
+        merkle_root: &[u8; 32],
+        challenge_evals: &[FieldElement],
+    ) -> (Transcript, FieldElement, FieldElement) {
+        let mut transcript = Transcript::new(b"endgame-rs-fri");
+        transcript.absorb(merkle_root);
+        transcript.absorb(&write_state(challenge_evals));
+        let gamma = transcript.challenge_field();
+        let correction = transcript.challenge_field();
+        (transcript, gamma, correction)
+    }
+
+    // Low-degree proof for the committed codeword and the challenge
+    // evaluations together (DEEP): FRI runs on the quotient codeword
+    // Σ_j γ^j (f(x) - v_j) / (x - z_j), which is low-degree only if f is
+    // and f(z_j) = v_j for every challenge. The quotient is degree
+    // corrected, so FRI proves the degree bound itself rather than the
+    // schedule's capacity. The verifier computes the first layer from f, so
+    // each query's first-layer fiber is opened in `tree`.
+    fn prove_low_degree(
+        &self,
+        tree: &MerkleTree,
+        challenge_points: &[FieldElement],
+        challenge_evals: &[FieldElement],
+    ) -> (FriProof, Vec<ValuesAndPaths>) {
+        let (mut transcript, gamma, correction) =
+            Self::fri_transcript(&self.merkle_root, challenge_evals);
+        let schedule = self.fold_schedule();
+        let exponent = degree_correction(&schedule, self.degree_bound);
+        let quotient = self
+            .domain
+            .iter()
+            .zip(&self.evaluations)
+            .map(|(&x, &y)| {
+                let q = deep_quotient(x, y, challenge_points, challenge_evals, gamma)
+                    .expect("Challenge points are drawn off the domain");
+                q * (FieldElement::one() + correction * x.pow(exponent))
+            })
+            .collect();
+        let (proof, fibers) = fri::prove(&mut transcript, quotient, self.domain[0], &schedule);
+        let openings = fibers
+            .iter()
+            .map(|fiber| {
+                let values = fiber.iter().map(|&i| self.evaluations[i]).collect();
+                let paths = fiber.iter().map(|&i| tree.generate_proof(i)).collect();
+                (values, paths)
+            })
+            .collect();
+        (proof, openings)
+    }
+
+    // FRI folding arities for the degree bound over this domain
+    fn fold_schedule(&self) -> Vec<usize> {
+        fri::fold_schedule(self.domain.len(), self.degree_bound)
+            .expect("Domain and degree bound were checked to have a schedule")
+    }
+
+    fn serialize_field_element(fe: &FieldElement) -> Vec<u8> {
+        let value = fe.value();
+        let mut result = vec![0u8; 8];
+        result.copy_from_slice(&value.to_le_bytes());
+        result
+    }
+
+    fn build_merkle_tree(&self) -> MerkleTree {
+        let leaves: Scratch<Vec<[u8; 8]>> = scratch(
+            self.evaluations
+                .iter()
+                .map(|eval| eval.value().to_le_bytes())
+                .collect(),
+        );
+
+        #[cfg(feature = "parallel")]
+        let tree = MerkleTree::from_fixed_leaves_parallel(&leaves);
+        #[cfg(not(feature = "parallel"))]
+        let tree = MerkleTree::from_fixed_leaves(&leaves);
+        tree
+    }
+
+    // Rough count of SHA-256 invocations `verify_proof` performs for this
+    // configuration: Merkle openings (leaf plus one per level), transcript
+    // draws, and the FRI queries at the degree bound
+    pub fn estimated_verify_hashes(&self) -> usize {
+        let depth = self.domain.len().next_power_of_two().trailing_zeros() as usize;
+        let openings = self.num_challenges * (1 + depth);
+        let challenges = 2 + self.num_challenges;
+        let low_degree = fri::estimated_verify_hashes(self.domain.len(), &self.fold_schedule());
+        openings + challenges + low_degree
+    }
+
+    // Folding challenge for two commitments (Fiat-Shamir), so a fold can be
+    // recomputed from the roots it records
+    pub fn fold_challenge(left_root: &[u8; 32], right_root: &[u8; 32]) -> FieldElement {
+        let mut transcript = Transcript::new(b"endgame-rs-fold");
+        transcript.absorb(left_root);
+        transcript.absorb(right_root);
+        transcript.challenge_field()
+    }
+
+    // Aggregates the commitments the accumulators hold. They must share this
+    // accumulator's domain and challenge count; every codeword is held to
+    // this accumulator's degree bound, so one committed under a looser bound
+    // makes the aggregate fail. The result verifies with `verify_aggregate`.
+    pub fn aggregate(&self, parts: &[&ReedSolomonAccumulator]) -> AggregateProof {
+        assert!(!parts.is_empty(), "Nothing to aggregate");
+        assert!(
+            parts
+                .iter()
+                .all(|acc| acc.domain == self.domain && acc.num_challenges == self.num_challenges),
+            "Aggregated accumulators must share a domain and challenge count"
+        );
+        assert!(
+            parts.iter().all(|acc| acc.degree > 0),
+            "Aggregated accumulators must hold a commitment"
+        );
+
+        let shift = self.domain[0];
+        let size = self.domain.len();
+        let roots: Vec<[u8; 32]> = parts.iter().map(|acc| acc.merkle_root).collect();
+        let points: Vec<Vec<FieldElement>> = roots
+            .iter()
+            .map(|root| Self::challenge_points(root, self.num_challenges, shift, size))
+            .collect();
+        let challenge_evals: Vec<Vec<FieldElement>> = parts
+            .iter()
+            .zip(&points)
+            .map(|(acc, points)| {
+                points
+                    .iter()
+                    .map(|&point| {
+                        acc.evaluate_at(point)
+                            .expect("Committed domain points must be distinct")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let (mut transcript, alphas, gamma, correction) =
+            Self::aggregate_transcript(&roots, &challenge_evals);
+        let schedule = self.fold_schedule();
+        let exponent = degree_correction(&schedule, self.degree_bound);
+        let combined = self
+            .domain
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let sum: FieldElement = parts
+                    .iter()
+                    .zip(&points)
+                    .zip(&challenge_evals)
+                    .zip(&alphas)
+                    .map(|(((acc, points), evals), &alpha)| {
+                        let q = deep_quotient(x, acc.evaluations[i], points, evals, gamma)
+                            .expect("Challenge points are drawn off the domain");
+                        alpha * q
+                    })
+                    .sum();
+                sum * (FieldElement::one() + correction * x.pow(exponent))
+            })
+            .collect();
+        let (fri, fibers) = fri::prove(&mut transcript, combined, shift, &schedule);
+
+        let trees: Vec<MerkleTree> = parts.iter().map(|acc| acc.build_merkle_tree()).collect();
+        let fri_openings = fibers
+            .iter()
+            .map(|fiber| {
+                parts
+                    .iter()
+                    .zip(&trees)
+                    .map(|(acc, tree)| {
+                        let values = fiber.iter().map(|&i| acc.evaluations[i]).collect();
+                        let paths = fiber.iter().map(|&i| tree.generate_proof(i)).collect();
+                        (values, paths)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        AggregateProof {
+            roots,
+            challenge_evals,
+            fri,
+            fri_openings,
+        }
+    }
+
+    // Checks an aggregate against this accumulator's domain, challenge
+    // count and degree bound. Passing means every root commits a codeword
+    // close to a polynomial within the degree bound that takes the claimed
+    // values at the root's challenge points, as `verify` on a proof for
+    // that root would show. `InvalidOpening` reports the position of the
+    // commitment whose opening isn't in its tree.
+    pub fn verify_aggregate(&self, proof: &AggregateProof) -> crate::error::Result<()> {
+        let parts = proof.roots.len();
+        if parts == 0
+            || proof.challenge_evals.len() != parts
+            || proof
+                .challenge_evals
+                .iter()
+                .any(|evals| evals.len() != self.num_challenges)
+        {
+            return Err(VerifyError::Malformed.into());
+        }
+        let schedule = self.fold_schedule();
+        if proof.fri.fold_factors() != schedule {
+            return Err(VerifyError::DegreeOutOfRange.into());
+        }
+        let size = self.domain.len();
+        if proof.fri_openings.len() != fri::query_count(size, &schedule)
+            || proof.fri_openings.iter().any(|query| query.len() != parts)
+        {
+            return Err(VerifyError::Malformed.into());
+        }
+
+        let shift = self.domain[0];
+        let points: Vec<Vec<FieldElement>> = proof
+            .roots
+            .iter()
+            .map(|root| Self::challenge_points(root, self.num_challenges, shift, size))
+            .collect();
+        let (mut transcript, alphas, gamma, correction) =
+            Self::aggregate_transcript(&proof.roots, &proof.challenge_evals);
+        let exponent = degree_correction(&schedule, self.degree_bound);
+
+        let mut leaf = scratch(Vec::with_capacity(8));
+        let mut invalid_part = None;
+        let low_degree = fri::verify(
+            &mut transcript,
+            &proof.fri,
+            shift,
+            size,
+            &schedule,
+            |query, indices, xs| {
+                let mut combined = vec![FieldElement::zero(); indices.len()];
+                for (part, (values, paths)) in proof.fri_openings[query].iter().enumerate() {
+                    if values.len() != indices.len() || paths.len() != indices.len() {
+                        return None;
+                    }
+                    for ((&index, value), path) in indices.iter().zip(values).zip(paths) {
+                        if !verify_leaf(&proof.roots[part], value, path, index, &mut leaf) {
+                            invalid_part = Some(part);
+                            return None;
+                        }
+                    }
+                    for ((sum, &x), &y) in combined.iter_mut().zip(xs).zip(values) {
+                        let q =
+                            deep_quotient(x, y, &points[part], &proof.challenge_evals[part], gamma)
+                                .ok()?;
+                        *sum = *sum + alphas[part] * q;
+                    }
+                }
+                Some(
+                    combined
+                        .iter()
+                        .zip(xs)
+                        .map(|(&sum, &x)| {
+                            sum * (FieldElement::one() + correction * x.pow(exponent))
+                        })
+                        .collect(),
+                )
+            },
+        );
+        if let Some(index) = invalid_part {
+            return Err(VerifyError::InvalidOpening { index }.into());
+        }
+        if !low_degree {
+            return Err(VerifyError::LowDegree.into());
+        }
+        Ok(())
+    }
+
+    // Transcript for an aggregate, binding every root and its claimed
+    // challenge evaluations; returns it with one combination coefficient
+    // per root, the DEEP combination challenge and the degree correction
+    // challenge
+    fn aggregate_transcript(
+        roots: &[[u8; 32]],
+        challenge_evals: &[Vec<FieldElement>],
+    ) -> (Transcript, Vec<FieldElement>, FieldElement, FieldElement) {
+        let mut transcript = Transcript::new(b"endgame-rs-aggregate");
+        for (root, evals) in roots.iter().zip(challenge_evals) {
+            transcript.absorb(root);
+            transcript.absorb(&write_state(evals));
+        }
+        let alphas = roots.iter().map(|_| transcript.challenge_field()).collect();
+        let gamma = transcript.challenge_field();
+        let correction = transcript.challenge_field();
+        (transcript, alphas, gamma, correction)
+    }
+
+    // `count` challenge points for a commitment over the coset of `size`
+    // points shifted by `shift`
+    fn challenge_points(
+        merkle_root: &[u8; 32],
+        count: usize,
+        shift: FieldElement,
+        size: usize,
+    ) -> Vec<FieldElement> {
+        let mut transcript = Self::challenge_transcript(merkle_root);
+        let coset_power = shift.pow(size as u64);
+        (0..count)
+            .map(|_| Self::next_challenge_point(&mut transcript, coset_power, size))
+            .collect()
+    }
+
+    // Next field element off the domain, whose points x are exactly those
+    // with x^size = `coset_power`. The DEEP quotient divides by x - z, so a
+    // challenge point on the domain is skipped.
+    fn next_challenge_point(
+        transcript: &mut Transcript,
+        coset_power: FieldElement,
+        size: usize,
+    ) -> FieldElement {
+        loop {
+            let point: FieldElement = transcript.challenge_field();
+            if point.pow(size as u64) != coset_power {
+                return point;
+            }
+        }
+    }
+
+    fn challenge_transcript(merkle_root: &[u8; 32]) -> Transcript {
+        let mut transcript = Transcript::new(b"endgame-rs-challenges");
+        transcript.absorb(merkle_root);
+        transcript
+    }
+
+    // Verifies each proof against this accumulator's state, reporting the
+    // index of the first one that fails
+    pub fn verify_batch(&self, proofs: &[RSProof]) -> Result<(), usize> {
+        match proofs.iter().position(|proof| !self.verify(proof)) {
+            Some(index) => Err(index),
+            None => Ok(()),
+        }
+    }
+
+    // Same result as `verify_batch`, with the proofs checked on the rayon
+    // pool. Verification only reads the accumulator, so it is shared as is.
+    #[cfg(feature = "parallel")]
+    pub fn verify_batch_parallel(&self, proofs: &[RSProof]) -> Result<(), usize> {
+        match proofs
+            .par_iter()
+            .position_first(|proof| !self.verify(proof))
+        {
+            Some(index) => Err(index),
+            None => Ok(()),
+        }
+    }
+
+    // Light-client verification from the proof alone: the proof is well
+    // formed, its challenge points are the Fiat-Shamir ones for its root,
+    // every opening is in the committed tree, and FRI on the DEEP quotient
+    // shows the committed codeword is close to a low-degree polynomial that
+    // takes the claimed challenge evaluations. The verifier is configured
+    // like `new()`: a proof over another domain size or with another
+    // challenge count is a `ParamMismatch`, one claiming more than
+    // `DEFAULT_DEGREE_BOUND` coefficients is `DegreeOutOfRange`, and either
+    // is checked with `verify` on an accumulator built like the prover's.
+    pub fn verify_proof(proof: &RSProof) -> crate::error::Result<()> {
+        if proof.domain_size != EVAL_DOMAIN_SIZE || proof.num_challenges != NUM_CHALLENGES {
+            return Err(VerifyError::ParamMismatch.into());
+        }
+        Ok(Self::check_proof(
+            proof,
+            FieldElement::one(),
+            DEFAULT_DEGREE_BOUND,
+        )?)
+    }
+
+    // For a root obtained elsewhere, e.g. from a block header: the proof
+    // must be for that root and verify against this accumulator's state
+    pub fn verify_against_root(
+        &self,
+        proof: &RSProof,
+        expected_root: &[u8],
+    ) -> crate::error::Result<()> {
+        if proof.merkle_root.as_slice() != expected_root {
+            return Err(VerifyError::RootMismatch.into());
+        }
+        Ok(self.verify_against_state(proof)?)
+    }
+
+    // Full verification, plus a check that the proof opens domain position
+    // `index` to `claimed`
+    pub fn verify_opening(
+        &self,
+        proof: &RSProof,
+        index: usize,
+        claimed: FieldElement,
+    ) -> crate::error::Result<()> {
+        self.verify_against_state(proof)?;
+        let position = proof
+            .eval_indices
+            .iter()
+            .position(|&i| i == index)
+            .ok_or(VerifyError::NotOpened { index })?;
+        if proof.domain_evals[position] != claimed {
+            return Err(VerifyError::ValueMismatch { index }.into());
+        }
+        Ok(())
+    }
+
+    // `Accumulator::verify`, saying which check failed
+    pub fn check(&self, proof: &RSProof) -> crate::error::Result<()> {
+        Ok(self.verify_against_state(proof)?)
+    }
+
+    // Full verification behind `Accumulator::verify`
+    fn verify_against_state(&self, proof: &RSProof) -> Result<(), VerifyError> {
+        if proof.domain_size != self.domain.len() || proof.num_challenges != self.num_challenges {
+            return Err(VerifyError::ParamMismatch);
+        }
+        // With or without the state, a proof only speaks for this commitment
+        if proof.merkle_root != self.merkle_root {
+            return Err(VerifyError::RootMismatch);
+        }
+        if !self.has_state() {
+            return Self::check_proof(proof, self.domain[0], self.degree_bound);
+        }
+
+        let schedule = Self::check_openings(proof, self.domain[0], self.degree_bound)?;
+
+        // Verify polynomial evaluations, comparing in constant time and only
+        // looking at the combined result. The low-degree proof checks them
+        // too, but against the state a wrong one is reported as such.
+        let mut evals_match = Choice::from(1);
+        for (i, &point) in proof.challenge_points.iter().enumerate() {
+            let expected = proof.challenge_evals[i];
+            let computed = self
+                .evaluate_at(point)
+                .map_err(|_| VerifyError::DegenerateDomain)?;
+            evals_match &= expected.ct_eq(&computed);
+        }
+        if !bool::from(evals_match) {
+            return Err(VerifyError::EvaluationMismatch);
+        }
+        Self::check_low_degree(proof, self.domain[0], &schedule)
+    }
+
+    // `bound` is the verifier's own: the prover's bound may not exceed it,
+    // and the FRI proof must fold by the verifier's schedule for it. The
+    // degree correction then makes FRI prove the prover's bound exactly.
+    fn check_degree_bound(proof: &RSProof, bound: usize) -> Result<Vec<usize>, VerifyError> {
+        if proof.degree_bound == 0 || proof.degree_bound > bound {
+            return Err(VerifyError::DegreeOutOfRange);
+        }
+        match fri::fold_schedule(proof.domain_size, bound) {
+            Some(schedule) if schedule == proof.fri.fold_factors() => Ok(schedule),
+            _ => Err(VerifyError::DegreeOutOfRange),
+        }
+    }
+
+    // Shared by `verify_proof` and `verify_against_state`, for a domain
+    // that is the coset of the proof's size shifted by `shift` and a
+    // verifier with degree bound `bound`
+    fn check_proof(proof: &RSProof, shift: FieldElement, bound: usize) -> Result<(), VerifyError> {
+        let schedule = Self::check_openings(proof, shift, bound)?;
+        Self::check_low_degree(proof, shift, &schedule)
+    }
+
+    // Everything up to the low-degree proof: shape, degree bound, the
+    // Fiat-Shamir challenges and indices, and the openings. Returns the
+    // folding schedule FRI must follow.
+    fn check_openings(
+        proof: &RSProof,
+        shift: FieldElement,
+        bound: usize,
+    ) -> Result<Vec<usize>, VerifyError> {
+        let openings = proof.eval_indices.len();
+        if proof.domain_evals.len() != openings
+            || proof.merkle_proofs.len() != openings
+            || proof.challenge_evals.len() != proof.challenge_points.len()
+            || proof.challenge_points.len() != proof.num_challenges
+            || proof.eval_indices.iter().any(|&i| i >= proof.domain_size)
+        {
+            return Err(VerifyError::Malformed);
+        }
+        let schedule = Self::check_degree_bound(proof, bound)?;
+
+        // These loops run without allocating per point: the challenges and
+        // indices are drawn as they are compared and every leaf goes
+        // through one buffer
+        let mut transcript = Self::challenge_transcript(&proof.merkle_root);
+        let coset_power = shift.pow(proof.domain_size as u64);
+        if proof.challenge_points.iter().any(|&point| {
+            point != Self::next_challenge_point(&mut transcript, coset_power, proof.domain_size)
+        }) {
+            return Err(VerifyError::ChallengeMismatch);
+        }
+
+        // The openings must be at the positions drawn from the root, or a
+        // prover could open only positions it has made consistent
+        let count = proof.num_challenges.min(proof.domain_size);
+        if openings != count {
+            return Err(VerifyError::IndexMismatch);
+        }
+        let mut transcript = Self::index_transcript(&proof.merkle_root);
+        for (k, &index) in proof.eval_indices.iter().enumerate() {
+            let expected = Self::next_distinct_index(
+                &mut transcript,
+                proof.domain_size,
+                &proof.eval_indices[..k],
+            );
+            if index != expected {
+                return Err(VerifyError::IndexMismatch);
+            }
+        }
+
+        let mut leaf = scratch(Vec::with_capacity(8));
+        for index in 0..openings {
+            if !proof.verify_opening_with(index, &proof.merkle_root, &mut leaf) {
+                return Err(VerifyError::InvalidOpening { index });
+            }
+        }
+        Ok(schedule)
+    }
+
+    // FRI on the DEEP quotient. Its first layer is computed from the
+    // committed codeword, so every fiber it folds must be opened under the
+    // proof's root.
+    fn check_low_degree(
+        proof: &RSProof,
+        shift: FieldElement,
+        schedule: &[usize],
+    ) -> Result<(), VerifyError> {
+        if proof.fri_openings.len() != fri::query_count(proof.domain_size, schedule) {
+            return Err(VerifyError::Malformed);
+        }
+        let (mut transcript, gamma, correction) =
+            Self::fri_transcript(&proof.merkle_root, &proof.challenge_evals);
+        let exponent = degree_correction(schedule, proof.degree_bound);
+        let mut leaf = scratch(Vec::with_capacity(8));
+        let low_degree = fri::verify(
+            &mut transcript,
+            &proof.fri,
+            shift,
+            proof.domain_size,
+            schedule,
+            |query, indices, xs| {
+                let (values, paths) = &proof.fri_openings[query];
+                if values.len() != indices.len() || paths.len() != indices.len() {
+                    return None;
+                }
+                for ((&index, value), path) in indices.iter().zip(values).zip(paths) {
+                    if !verify_leaf(&proof.merkle_root, value, path, index, &mut leaf) {
+                        return None;
+                    }
+                }
+                xs.iter()
+                    .zip(values)
+                    .map(|(&x, &y)| {
+                        let q = deep_quotient(
+                            x,
+                            y,
+                            &proof.challenge_points,
+                            &proof.challenge_evals,
+                            gamma,
+                        )
+                        .ok()?;
+                        Some(q * (FieldElement::one() + correction * x.pow(exponent)))
+                    })
+                    .collect()
+            },
+        );
+        if !low_degree {
+            return Err(VerifyError::LowDegree);
+        }
+        Ok(())
+    }
+}
+
+impl Default for ReedSolomonAccumulator {
+    fn default() -> Self {
+        <Self as Accumulator>::new()
+    }
+}
+
+impl Accumulator for ReedSolomonAccumulator {
+    type Proof = RSProof;
+    type State = Vec<FieldElement>;
+    type FoldProof = FoldProof;
+
+    fn new() -> Self {
+        Self::with_params(EVAL_DOMAIN_SIZE, DEFAULT_DEGREE_BOUND, NUM_CHALLENGES)
+    }
+
+    fn accumulate(&mut self, state: Self::State) -> Self::Proof {
+        self.try_accumulate(state)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn verify(&self, proof: &Self::Proof) -> bool {
+        self.verify_against_state(proof).is_ok()
+    }
+
+    fn proof_size(proof: &Self::Proof) -> usize {
+        proof.size_bytes()
+    }
+
+    fn fold(&mut self, other: &Self) -> Self::FoldProof {
+        assert!(
+            self.has_state() && other.has_state(),
+            "Folding needs both codewords"
+        );
+        assert!(
+            self.domain == other.domain,
+            "Folded accumulators must share a domain"
+        );
+        // The combined proof is made under this accumulator's parameters,
+        // which must then hold for the other codeword too
+        assert!(
+            self.degree_bound == other.degree_bound && self.num_challenges == other.num_challenges,
+            "Folded accumulators must share a degree bound and challenge count"
+        );
+        let left_root = self.merkle_root;
+        let right_root = other.merkle_root;
+        let alpha = Self::fold_challenge(&left_root, &right_root);
+        let max_deg = self.degree.max(other.degree);
+
+        // Both codewords are full evaluations over the shared domain, so
+        // combining them position by position gives the codeword of
+        // P1 + alpha * P2, whose degree is at most the larger of the two
+        for (eval, &other_eval) in self.evaluations.iter_mut().zip(&other.evaluations) {
+            *eval = *eval + alpha * other_eval;
+        }
+        self.degree = max_deg;
+
+        let tree = self.commit_codeword();
+        let combined = self.prove_with(&tree);
+        FoldProof {
+            combined,
+            left_root,
+            right_root,
+            alpha,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::fmt::Write;
+
+    // Helper for debug hex printing
+    fn hex_str(bytes: &[u8]) -> String {
+        let mut s = String::with_capacity(2 * bytes.len());
+        for b in bytes {
+            write!(s, "{:02x}", b).unwrap();
+        }
+        s
+    }
+
+    #[test]
+    fn test_serialization_consistency() {
+        let fe = FieldElement::new(123);
+        let bytes = ReedSolomonAccumulator::serialize_field_element(&fe);
+        assert_eq!(bytes.len(), 8, "Serialized field element should be 8 bytes");
+
+        // Verify value can be recovered
+        let mut value_bytes = [0u8; 8];
+        value_bytes.copy_from_slice(&bytes);
+        let value = u64::from_le_bytes(value_bytes);
+        assert_eq!(value, fe.value());
+    }
+
+    #[test]
+    fn test_merkle_tree_basic() {
+        println!("\nRunning basic Merkle tree test");
+
+        let leaves: Vec<Vec<u8>> = vec![vec![1, 0, 0, 0, 0, 0, 0, 0], vec![2, 0, 0, 0, 0, 0, 0, 0]];
+
+        println!("Building tree with leaves:");
+        for (i, leaf) in leaves.iter().enumerate() {
+            println!("Leaf {}: {}", i, hex_str(leaf));
+        }
+
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+        println!("Tree root: {}", hex_str(root));
+
+        let proof = tree.generate_proof(0);
+        let verified = MerkleTree::verify_proof(root, &leaves[0], &proof, 0);
+        assert!(verified, "Basic Merkle proof verification failed");
+    }
+
+    #[test]
+    fn test_merkle_proof_verification() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let state = vec![FieldElement::new(1), FieldElement::new(2)];
+        let proof = acc.accumulate(state);
+        assert!(acc.verify(&proof), "Basic Merkle proof verification failed");
+    }
+
+    #[test]
+    fn test_accumulator_basic() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let state = vec![
+            FieldElement::new(1),
+            FieldElement::new(2),
+            FieldElement::new(3),
+        ];
+        let proof = acc.accumulate(state);
+        assert!(acc.verify(&proof), "Basic test failed");
+    }
+
+    #[test]
+    fn test_accumulator_fold() {
+        let mut acc1 = ReedSolomonAccumulator::new();
+        let mut acc2 = ReedSolomonAccumulator::new();
+
+        let state1 = vec![FieldElement::new(1), FieldElement::new(2)];
+        let state2 = vec![FieldElement::new(3), FieldElement::new(4)];
+
+        let proof1 = acc1.accumulate(state1);
+        assert!(
+            acc1.verify(&proof1),
+            "First accumulator verification failed"
+        );
+
+        let proof2 = acc2.accumulate(state2);
+        assert!(
+            acc2.verify(&proof2),
+            "Second accumulator verification failed"
+        );
+
+        let folded_proof = acc1.fold(&acc2);
+        assert!(
+            acc1.verify(&folded_proof.combined),
+            "Folded verification failed"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must share a degree bound and challenge count")]
+    fn test_fold_rejects_other_parameters() {
+        let mut narrow = ReedSolomonAccumulator::with_params(462, 8, 2);
+        narrow.accumulate(vec![FieldElement::new(1)]);
+        let mut wide = ReedSolomonAccumulator::with_params(462, 64, 2);
+        wide.accumulate((1..=40).map(FieldElement::new).collect());
+        narrow.fold(&wide);
+    }
+
+    #[test]
+    fn test_fold_records_inputs() {
+        let mut left = ReedSolomonAccumulator::new();
+        let mut right = ReedSolomonAccumulator::new();
+        let left_proof = left.accumulate((1..=3).map(FieldElement::new).collect());
+        let right_proof = right.accumulate((4..=8).map(FieldElement::new).collect());
+
+        let left_before = left.clone();
+        let folded = left.fold(&right);
+        assert_eq!(folded.left_root, left_proof.merkle_root());
+        assert_eq!(folded.right_root, right_proof.merkle_root());
+        assert_eq!(
+            folded.alpha,
+            ReedSolomonAccumulator::fold_challenge(&folded.left_root, &folded.right_root)
+        );
+        assert_eq!(folded.combined.merkle_root(), left.merkle_root());
+        assert!(left.verify(&folded.combined));
+
+        // The combined state is left + alpha * right on the first five
+        // points, where the left polynomial continues past its own three values
+        let mut expected = ReedSolomonAccumulator::new();
+        let combined_state = (0..5)
+            .map(|i| left_before.evaluations[i] + folded.alpha * FieldElement::new(i as u64 + 4))
+            .collect();
+        assert_eq!(expected.accumulate(combined_state), folded.combined);
+    }
+
+    #[test]
+    fn test_fold_of_unequal_degrees_is_the_combined_polynomial() {
+        let mut left = ReedSolomonAccumulator::with_params(66, 16, 2);
+        let mut right = ReedSolomonAccumulator::with_params(66, 16, 2);
+        left.accumulate((1..=2).map(FieldElement::new).collect());
+        right.accumulate((3..=12).map(|i| FieldElement::new(i * i)).collect());
+        let left_before = left.clone();
+
+        let folded = left.fold(&right);
+        assert_eq!(left.degree(), 10);
+        assert!(left.verify(&folded.combined));
+        let points = left
+            .domain
+            .iter()
+            .copied()
+            .chain([123_456, 7_654_321].map(FieldElement::new));
+        for x in points {
+            assert_eq!(
+                left.evaluate_at(x).unwrap(),
+                left_before.evaluate_at(x).unwrap() + folded.alpha * right.evaluate_at(x).unwrap()
+            );
+        }
+    }
+
+    // Value at `x` of the polynomial through the given points
+    fn interpolate(points: &[(FieldElement, FieldElement)], x: FieldElement) -> FieldElement {
+        points
+            .iter()
+            .map(|&(xi, yi)| {
+                points
+                    .iter()
+                    .filter(|&&(xj, _)| xj != xi)
+                    .fold(yi, |acc, &(xj, _)| acc * (x - xj) / (xi - xj))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_state_is_reed_solomon_encoded() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 2);
+        let state: Vec<FieldElement> = [9, 4, 7, 1, 3].into_iter().map(FieldElement::new).collect();
+        let proof = acc.accumulate(state.clone());
+        assert!(acc.verify(&proof));
+
+        assert_eq!(acc.evaluations.len(), 42);
+        assert_eq!(acc.evaluations[..state.len()], state[..]);
+
+        // Any `degree_bound` positions of the codeword decode back to the state
+        let tail: Vec<(FieldElement, FieldElement)> = acc.domain[34..]
+            .iter()
+            .copied()
+            .zip(acc.evaluations[34..].iter().copied())
+            .collect();
+        for (i, &value) in state.iter().enumerate() {
+            assert_eq!(interpolate(&tail, acc.domain[i]), value);
+        }
+    }
+
+    #[test]
+    fn test_default_matches_new() {
+        let state: Vec<FieldElement> = (1..=3).map(FieldElement::new).collect();
+        let proof = ReedSolomonAccumulator::default().accumulate(state.clone());
+        assert_eq!(proof, ReedSolomonAccumulator::new().accumulate(state));
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_accumulator() {
+        let state: Vec<FieldElement> = (1..=7).map(FieldElement::new).collect();
+        let mut fresh = ReedSolomonAccumulator::with_params(66, 16, 3);
+
+        let mut reused = ReedSolomonAccumulator::with_params(66, 16, 3);
+        reused.accumulate((10..=20).map(FieldElement::new).collect());
+        let other = reused.clone();
+        reused.fold(&other);
+        reused.reset();
+
+        assert_eq!(reused.degree(), 0);
+        assert_eq!(reused.evaluations, fresh.evaluations);
+        assert_eq!(reused.merkle_root(), fresh.merkle_root());
+        assert_eq!(reused.accumulate(state.clone()), fresh.accumulate(state));
+    }
+
+    #[test]
+    fn test_param_mismatch_is_reported() {
+        let state: Vec<FieldElement> = (1..=5).map(FieldElement::new).collect();
+        let proof = ReedSolomonAccumulator::with_params(462, 64, 16).accumulate(state.clone());
+
+        let mut verifier = ReedSolomonAccumulator::new();
+        verifier.accumulate(state.clone());
+        assert_eq!(
+            verifier.verify_against_state(&proof),
+            Err(VerifyError::ParamMismatch)
+        );
+        assert!(!verifier.verify(&proof));
+        // The light-client verifier has the default configuration
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&proof),
+            Err(VerifyError::ParamMismatch.into())
+        );
+
+        let mut smaller = ReedSolomonAccumulator::with_params(154, 64, NUM_CHALLENGES);
+        smaller.accumulate(state.clone());
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&smaller.prove()),
+            Err(VerifyError::ParamMismatch.into())
+        );
+        let proof = verifier.prove();
+        assert_eq!(
+            smaller.verify_against_state(&proof),
+            Err(VerifyError::ParamMismatch)
+        );
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+
+        // The embedded parameters must agree with the proof's own contents
+        let mut three = ReedSolomonAccumulator::with_params(EVAL_DOMAIN_SIZE, 64, 3);
+        three.accumulate(state);
+        let mut tampered = proof;
+        tampered.num_challenges = 3;
+        assert_eq!(
+            three.verify_against_state(&tampered),
+            Err(VerifyError::Malformed)
+        );
+    }
+
+    fn proof_batch(acc: &mut ReedSolomonAccumulator) -> Vec<RSProof> {
+        let proof = acc.accumulate((1..=9).map(FieldElement::new).collect());
+        vec![proof; 5]
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let mut batch = proof_batch(&mut acc);
+        assert_eq!(acc.verify_batch(&batch), Ok(()));
+        assert_eq!(acc.verify_batch(&[]), Ok(()));
+
+        batch[4].domain_evals[1] = FieldElement::zero();
+        batch[2].challenge_evals[0] = FieldElement::zero();
+        assert_eq!(acc.verify_batch(&batch), Err(2));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_verify_batch_parallel_matches_sequential() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let mut batch = proof_batch(&mut acc);
+        assert_eq!(acc.verify_batch_parallel(&batch), Ok(()));
+
+        batch[3].merkle_proofs[0][0][0] ^= 1;
+        assert_eq!(acc.verify_batch_parallel(&batch), Err(3));
+        assert_eq!(acc.verify_batch_parallel(&batch), acc.verify_batch(&batch));
+    }
+
+    #[test]
+    fn test_commitment_eq() {
+        let state: Vec<FieldElement> = (1..=6).map(FieldElement::new).collect();
+        let mut a = ReedSolomonAccumulator::new();
+        let mut b = ReedSolomonAccumulator::new();
+        a.accumulate(state.clone());
+        b.accumulate(state.clone());
+        assert!(a.commitment_eq(&b));
+
+        let mut c = ReedSolomonAccumulator::new();
+        c.accumulate((2..=7).map(FieldElement::new).collect());
+        assert!(!a.commitment_eq(&c));
+
+        // Same state over another domain is another codeword
+        let mut d = ReedSolomonAccumulator::with_params(154, 64, NUM_CHALLENGES);
+        d.accumulate(state);
+        assert!(!a.commitment_eq(&d));
+    }
+
+    #[test]
+    fn test_grow_domain() {
+        let mut acc = ReedSolomonAccumulator::with_params(14, 4, 2);
+        acc.accumulate((1..=4).map(FieldElement::new).collect());
+        let before = acc.clone();
+        let larger: Vec<FieldElement> = (1..=10).map(FieldElement::new).collect();
+        assert!(acc.clone().try_accumulate(larger.clone()).is_err());
+
+        for new_size in [7, 21, 28] {
+            assert_eq!(
+                acc.grow_domain(new_size),
+                Err(RSError::InvalidGrowth { size: 14, new_size }.into())
+            );
+        }
+        acc.grow_domain(42).unwrap();
+        assert_eq!(acc.domain_size(), 42);
+        for i in 0..14 {
+            assert_eq!(acc.domain[3 * i], before.domain[i]);
+            assert_eq!(acc.evaluations[3 * i], before.evaluations[i]);
+        }
+        for (&x, &y) in acc.domain.iter().zip(&acc.evaluations) {
+            assert_eq!(acc.evaluate_at(x).unwrap(), y);
+        }
+        let regrown = acc.prove();
+        assert!(acc.verify(&regrown));
+
+        let proof = acc.accumulate(larger.clone());
+        assert!(acc.verify(&proof));
+        assert_eq!(acc.evaluations[..10], larger[..]);
+
+        // A commitment alone has no codeword to grow, and stays stateless
+        let mut light = ReedSolomonAccumulator::from_root(proof.merkle_root());
+        assert_eq!(
+            light.grow_domain(2 * EVAL_DOMAIN_SIZE),
+            Err(RSError::NoState.into())
+        );
+        assert!(!light.has_state());
+        assert_eq!(light.domain_size(), EVAL_DOMAIN_SIZE);
+
+        // A bound with no blowup left, as a deserialized accumulator may
+        // hold, is refused before anything changes
+        let mut unfoldable = ReedSolomonAccumulator::with_params(14, 4, 2);
+        unfoldable.degree_bound = 14;
+        assert_eq!(
+            unfoldable.grow_domain(42),
+            Err(RSError::NoFoldSchedule {
+                bound: 42,
+                domain_size: 42
+            }
+            .into())
+        );
+        assert_eq!(unfoldable.domain_size(), 14);
+        assert_eq!(unfoldable.degree_bound, 14);
+    }
+
+    #[test]
+    fn test_getters() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 2);
+        assert_eq!(acc.degree(), 0);
+        assert_eq!(acc.domain_size(), 42);
+
+        let proof = acc.accumulate((1..=3).map(FieldElement::new).collect());
+        assert_eq!(acc.degree(), 3);
+        assert_eq!(acc.domain_size(), 42);
+        assert_eq!(acc.merkle_root(), proof.merkle_root());
+    }
+
+    #[test]
+    fn test_proofs_are_deterministic() {
+        let state: Vec<FieldElement> = (1..=5).map(FieldElement::new).collect();
+        let proof = ReedSolomonAccumulator::new().accumulate(state.clone());
+        let again = ReedSolomonAccumulator::new().accumulate(state);
+        assert_eq!(proof, again);
+
+        let mut tampered = again.clone();
+        tampered.domain_evals[0] = tampered.domain_evals[0] + FieldElement::one();
+        assert_ne!(proof, tampered);
+
+        let mut tampered = again;
+        tampered.merkle_proofs[0][0][0] ^= 1;
+        assert_ne!(proof, tampered);
+    }
+
+    #[test]
+    fn test_reconstruct_after_erasures() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 2);
+        let state: Vec<FieldElement> = [5, 8, 13, 21, 34, 55]
+            .into_iter()
+            .map(FieldElement::new)
+            .collect();
+        acc.accumulate(state.clone());
+
+        // Keep every other evaluation, erasing half the codeword
+        let known: Vec<(usize, FieldElement)> = acc
+            .evaluations
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| i % 2 == 1)
+            .collect();
+        let recovered =
+            ReedSolomonAccumulator::reconstruct(&acc.domain, &known, state.len()).unwrap();
+        assert_eq!(recovered, acc.evaluations);
+        assert_eq!(recovered[..state.len()], state[..]);
+
+        // Duplicates don't count towards the needed points
+        let repeated = vec![known[0]; 10];
+        assert_eq!(
+            ReedSolomonAccumulator::reconstruct(&acc.domain, &repeated, state.len()),
+            Err(RSError::TooFewPoints {
+                known: 1,
+                needed: 6
+            }
+            .into())
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::reconstruct(&acc.domain, &[(42, FieldElement::one())], 1),
+            Err(RSError::IndexOutOfRange {
+                index: 42,
+                domain_size: 42
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn test_commit_then_open() {
+        let state: Vec<FieldElement> = (1..=4).map(FieldElement::new).collect();
+        let mut acc = ReedSolomonAccumulator::new();
+        let root = acc.commit(state.clone());
+
+        let proof = acc.prove();
+        assert_eq!(proof.merkle_root(), root);
+        assert!(acc.verify(&proof));
+
+        // Same proof as accumulating in one go
+        assert_eq!(proof, ReedSolomonAccumulator::new().accumulate(state));
+    }
+
+    #[test]
+    fn test_open_at_explicit_indices() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let root = acc.commit((1..=10).map(FieldElement::new).collect());
+
+        let indices = [3, 100, 255];
+        let opening = acc.open(&indices);
+        assert_eq!(opening.merkle_root(), root);
+        assert_eq!(opening.verify(&root), Ok(()));
+        for (i, (index, value)) in opening.openings().into_iter().enumerate() {
+            assert_eq!(index, indices[i]);
+            assert_eq!(value, acc.evaluations[index]);
+        }
+
+        let mut tampered = opening.clone();
+        tampered.values[2] = tampered.values[2] + FieldElement::one();
+        assert_eq!(
+            tampered.verify(&root),
+            Err(VerifyError::InvalidOpening { index: 2 }.into())
+        );
+        assert_eq!(
+            opening.verify(&[0; 32]),
+            Err(VerifyError::InvalidOpening { index: 0 }.into())
+        );
+    }
+
+    // `proof` with its openings swapped for `opening`'s
+    fn with_opening(mut proof: RSProof, opening: Opening) -> RSProof {
+        proof.eval_indices = opening.indices;
+        proof.domain_evals = opening.values;
+        proof.merkle_proofs = opening.paths;
+        proof
+    }
+
+    #[test]
+    fn test_proof_must_open_fiat_shamir_indices() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=10).map(FieldElement::new).collect());
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+
+        // Valid openings, but at positions the prover picked
+        let chosen = with_opening(proof.clone(), acc.open(&[0, 1]));
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&chosen),
+            Err(VerifyError::IndexMismatch.into())
+        );
+        assert_eq!(
+            acc.verify_against_state(&chosen),
+            Err(VerifyError::IndexMismatch)
+        );
+
+        // The right positions in the wrong order, or one of them dropped
+        let mut reordered = proof.clone();
+        reordered.eval_indices.reverse();
+        reordered.domain_evals.reverse();
+        reordered.merkle_proofs.reverse();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&reordered),
+            Err(VerifyError::IndexMismatch.into())
+        );
+        let mut dropped = proof;
+        dropped.eval_indices.pop();
+        dropped.domain_evals.pop();
+        dropped.merkle_proofs.pop();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&dropped),
+            Err(VerifyError::IndexMismatch.into())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Opening index out of range")]
+    fn test_open_rejects_out_of_range_index() {
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit(vec![FieldElement::one()]);
+        acc.open(&[EVAL_DOMAIN_SIZE]);
+    }
+
+    #[test]
+    fn test_verify_against_root() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let root = acc.commit((1..=5).map(FieldElement::new).collect());
+        let proof = acc.prove();
+        assert_eq!(acc.verify_against_root(&proof, &root), Ok(()));
+
+        let mut other_root = root;
+        other_root[0] ^= 1;
+        assert_eq!(
+            acc.verify_against_root(&proof, &other_root),
+            Err(VerifyError::RootMismatch.into())
+        );
+
+        // The expected root matching doesn't excuse a proof for a commitment
+        // other than the verifier's own
+        let mut other = ReedSolomonAccumulator::new();
+        other.accumulate((2..=6).map(FieldElement::new).collect());
+        assert_eq!(
+            other.verify_against_root(&proof, &root),
+            Err(VerifyError::RootMismatch.into())
+        );
+        assert!(!other.verify(&proof));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let verifier = ReedSolomonAccumulator::new();
+        let accs: Vec<ReedSolomonAccumulator> = (1..=3u64)
+            .map(|n| {
+                let mut acc = ReedSolomonAccumulator::new();
+                acc.commit((1..=n * 5).map(FieldElement::new).collect());
+                acc
+            })
+            .collect();
+        let parts: Vec<&ReedSolomonAccumulator> = accs.iter().collect();
+
+        let aggregate = verifier.aggregate(&parts);
+        let roots: Vec<[u8; 32]> = accs.iter().map(|acc| acc.merkle_root).collect();
+        assert_eq!(aggregate.roots(), roots.as_slice());
+        assert_eq!(verifier.verify_aggregate(&aggregate), Ok(()));
+        // The claimed challenge values are each root's own proof's
+        for (evals, acc) in aggregate.challenge_evals.iter().zip(&accs) {
+            assert_eq!(evals, &acc.prove().challenge_evals);
+        }
+
+        // The FRI challenges follow the claimed values, so changing one moves
+        // the queries away from the opened fibers
+        let mut wrong_eval = aggregate.clone();
+        wrong_eval.challenge_evals[1][0] = wrong_eval.challenge_evals[1][0] + FieldElement::one();
+        assert_eq!(
+            verifier.verify_aggregate(&wrong_eval),
+            Err(VerifyError::InvalidOpening { index: 0 }.into())
+        );
+
+        let mut wrong_opening = aggregate.clone();
+        let value = &mut wrong_opening.fri_openings[0][2].0[0];
+        *value = *value + FieldElement::one();
+        assert_eq!(
+            verifier.verify_aggregate(&wrong_opening),
+            Err(VerifyError::InvalidOpening { index: 2 }.into())
+        );
+
+        let mut truncated = aggregate.clone();
+        truncated.fri_openings.pop();
+        assert_eq!(
+            verifier.verify_aggregate(&truncated),
+            Err(VerifyError::Malformed.into())
+        );
+
+        // A commitment to a non-codeword can't be hidden in the combination
+        let mut corrupt = accs[1].clone();
+        for eval in corrupt.evaluations.iter_mut().skip(20) {
+            *eval = *eval + FieldElement::one();
+        }
+        corrupt.commit_codeword();
+        let aggregate = verifier.aggregate(&[&accs[0], &corrupt, &accs[2]]);
+        assert_eq!(
+            verifier.verify_aggregate(&aggregate),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_from_root_verifies_without_state() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=8).map(FieldElement::new).collect());
+
+        let light = ReedSolomonAccumulator::from_root(proof.merkle_root());
+        assert!(light.verify(&proof));
+        assert_eq!(light.degree(), 0);
+
+        let other = ReedSolomonAccumulator::from_root([7; 32]);
+        assert_eq!(
+            other.verify_against_state(&proof),
+            Err(VerifyError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn test_degree_bound_enforced() {
+        let mut acc = ReedSolomonAccumulator::with_params(462, 8, 2);
+        let proof = acc.accumulate((1..=8).map(FieldElement::new).collect());
+        assert_eq!(proof.degree_bound, 8);
+        assert_eq!(acc.verify_against_state(&proof), Ok(()));
+
+        let mut over = proof.clone();
+        over.degree_bound = 9;
+        assert_eq!(
+            acc.verify_against_state(&over),
+            Err(VerifyError::DegreeOutOfRange)
+        );
+
+        // A proof for 64 coefficients claimed under a bound of 8 folds by
+        // the wrong schedule for this verifier. The default verifier's
+        // schedule matches, but the degree correction for 8 coefficients
+        // exposes the polynomial's actual degree.
+        let mut wide = ReedSolomonAccumulator::with_params(462, 64, 2);
+        let mut forged = wide.accumulate((1..=64).map(FieldElement::new).collect());
+        forged.degree_bound = 8;
+        assert_eq!(
+            ReedSolomonAccumulator::check_degree_bound(&forged, acc.degree_bound),
+            Err(VerifyError::DegreeOutOfRange)
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&forged),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_verify_proof_enforces_default_degree_bound() {
+        let proof =
+            ReedSolomonAccumulator::new().accumulate((1..=9).map(FieldElement::new).collect());
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+
+        // An honest proof under a larger bound, which the default verifier
+        // doesn't accept however the proof folds
+        let mut loose = ReedSolomonAccumulator::with_params(EVAL_DOMAIN_SIZE, 70, NUM_CHALLENGES);
+        let over = loose.accumulate((1..=70).map(FieldElement::new).collect());
+        assert!(loose.verify(&over));
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&over),
+            Err(VerifyError::DegreeOutOfRange.into())
+        );
+
+        let mut claimed = proof;
+        claimed.degree_bound = DEFAULT_DEGREE_BOUND + 1;
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&claimed),
+            Err(VerifyError::DegreeOutOfRange.into())
+        );
+
+        // 66 coefficients fold by the default schedule too, but not under
+        // the default bound of 64
+        let mut capacity =
+            ReedSolomonAccumulator::with_params(EVAL_DOMAIN_SIZE, 66, NUM_CHALLENGES);
+        let mut hidden = capacity.accumulate((1..=66).map(FieldElement::new).collect());
+        assert_eq!(hidden.fri.fold_factors(), [2, 3, 11]);
+        hidden.degree_bound = DEFAULT_DEGREE_BOUND;
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&hidden),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_challenge_evaluations_must_match_openings() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=4).map(FieldElement::new).collect());
+        let mut other = ReedSolomonAccumulator::new();
+        other.commit(vec![FieldElement::new(8); 4]);
+
+        // The openings are of the committed polynomial, the challenge
+        // evaluations of another one. The DEEP quotient of the committed
+        // codeword by those evaluations isn't low-degree.
+        let mut mixed = proof.clone();
+        mixed.challenge_evals = mixed
+            .challenge_points
+            .iter()
+            .map(|&x| other.evaluate_at(x).unwrap())
+            .collect();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&mixed),
+            Err(VerifyError::LowDegree.into())
+        );
+        assert_eq!(
+            acc.verify_against_state(&mixed),
+            Err(VerifyError::EvaluationMismatch)
+        );
+
+        // An opened value from the other polynomial isn't in the commitment
+        let mut mixed = proof;
+        mixed.domain_evals[1] = other.evaluations[mixed.eval_indices[1]];
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&mixed),
+            Err(VerifyError::InvalidOpening { index: 1 }.into())
+        );
+    }
+
+    #[test]
+    fn test_degenerate_domain_fails_verification() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=4).map(FieldElement::new).collect());
+        acc.domain[1] = acc.domain[0];
+        assert_eq!(
+            acc.verify_against_state(&proof),
+            Err(VerifyError::DegenerateDomain)
+        );
+    }
+
+    #[test]
+    fn test_openings() {
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit(vec![FieldElement::new(7), FieldElement::new(9)]);
+        let proof = acc.prove();
+        let expected: Vec<(usize, FieldElement)> = proof
+            .eval_indices
+            .iter()
+            .map(|&i| (i, acc.evaluations[i]))
+            .collect();
+        assert_eq!(proof.openings(), Ok(expected));
+
+        let mut malformed = proof.clone();
+        malformed.domain_evals.pop();
+        assert_eq!(malformed.openings(), Err(VerifyError::Malformed.into()));
+    }
+
+    #[test]
+    fn test_verify_opening() {
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit((1..=5).map(FieldElement::new).collect());
+        let proof = acc.prove();
+
+        for &index in &proof.eval_indices {
+            let value = acc.evaluate_at(acc.domain()[index]).unwrap();
+            assert_eq!(acc.verify_opening(&proof, index, value), Ok(()));
+            assert_eq!(
+                acc.verify_opening(&proof, index, value + FieldElement::one()),
+                Err(VerifyError::ValueMismatch { index }.into())
+            );
+        }
+        let unopened = (0..).find(|i| !proof.eval_indices.contains(i)).unwrap();
+        assert_eq!(
+            acc.verify_opening(&proof, unopened, acc.evaluations[unopened]),
+            Err(VerifyError::NotOpened { index: unopened }.into())
+        );
+    }
+
+    #[test]
+    fn test_default_indices_follow_commitment() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 42);
+        acc.commit((1..=4).map(FieldElement::new).collect());
+        let indices = acc.default_indices();
+
+        // Every position exactly once when there are as many challenges as points
+        let mut sorted = indices.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..42).collect::<Vec<_>>());
+
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=4).map(FieldElement::new).collect());
+        assert_eq!(proof.eval_indices, acc.default_indices());
+        assert_ne!(proof.eval_indices[0], proof.eval_indices[1]);
+
+        let mut other = ReedSolomonAccumulator::new();
+        other.commit((5..=8).map(FieldElement::new).collect());
+        assert_ne!(other.default_indices(), acc.default_indices());
+        assert!(other
+            .default_indices()
+            .iter()
+            .all(|&i| i < EVAL_DOMAIN_SIZE));
+    }
+
+    #[test]
+    fn test_multiplicative_domain() {
+        let mut acc = ReedSolomonAccumulator::new_multiplicative(42);
+        assert_eq!(acc.domain_size(), 42);
+        assert_eq!(acc.domain[0], FieldElement::one());
+        let mut distinct = acc.domain.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 42);
+        // The domain is a subgroup: its generator has order exactly 42
+        assert_eq!(acc.domain[1].pow(42), FieldElement::one());
+
+        let state: Vec<FieldElement> = [3, 1, 4, 1, 5, 9]
+            .into_iter()
+            .map(FieldElement::new)
+            .collect();
+        let proof = acc.accumulate(state.clone());
+        assert!(acc.verify(&proof));
+        assert_eq!(acc.evaluations[..state.len()], state[..]);
+        for (&x, &y) in acc.domain.iter().zip(&acc.evaluations) {
+            assert_eq!(acc.evaluate_at(x).unwrap(), y);
+        }
+    }
+
+    #[test]
+    fn test_invalid_domain_parameters_rejected() {
+        let mut domain = fri::domain(6);
+        domain[3] = domain[1];
+        assert_eq!(
+            ReedSolomonAccumulator::from_domain(domain, 2, 1).unwrap_err(),
+            Error::Accumulator(RSError::DuplicateDomainPoint {
+                value: fri::domain(6)[1].value()
+            })
+        );
+
+        let domain = fri::domain(6);
+        assert_eq!(
+            ReedSolomonAccumulator::from_domain(domain.clone(), 0, 1).unwrap_err(),
+            Error::Accumulator(RSError::ZeroDegreeBound)
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::from_domain(domain.clone(), 7, 1).unwrap_err(),
+            Error::Accumulator(RSError::DegreeBoundTooLarge {
+                bound: 7,
+                domain_size: 6
+            })
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::from_domain(domain.clone(), 2, 0).unwrap_err(),
+            Error::Accumulator(RSError::ZeroChallenges)
+        );
+        // Folding 4 coefficients takes all six points, leaving no blowup
+        assert_eq!(
+            ReedSolomonAccumulator::from_domain(domain.clone(), 4, 1).unwrap_err(),
+            Error::Accumulator(RSError::NoFoldSchedule {
+                bound: 4,
+                domain_size: 6
+            })
+        );
+        let mut acc = ReedSolomonAccumulator::from_domain(domain, 2, 1).unwrap();
+        let proof = acc.accumulate(vec![FieldElement::new(3), FieldElement::new(8)]);
+        assert!(acc.verify(&proof));
+    }
+
+    #[test]
+    fn test_custom_domain() {
+        let shift = FieldElement::new(3);
+        let domain = fri::coset(shift, 42);
+        let mut acc = ReedSolomonAccumulator::with_domain(domain.clone()).unwrap();
+        assert_eq!(acc.domain(), &domain[..]);
+
+        let state = vec![FieldElement::new(11), FieldElement::new(22)];
+        let proof = acc.accumulate(state.clone());
+        assert!(acc.verify(&proof));
+        assert_eq!(acc.evaluations[..2], state[..]);
+        for (&x, &y) in acc.domain().iter().zip(&acc.evaluations) {
+            assert_eq!(acc.evaluate_at(x).unwrap(), y);
+        }
+
+        assert_eq!(
+            ReedSolomonAccumulator::with_domain(vec![]).unwrap_err(),
+            Error::Accumulator(RSError::EmptyDomain)
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::with_domain(vec![shift; 2]).unwrap_err(),
+            Error::Accumulator(RSError::DuplicateDomainPoint { value: 3 })
+        );
+        // Distinct points FRI can't fold: out of generator order, or no coset at all
+        let mut shuffled = domain;
+        shuffled.swap(1, 2);
+        assert_eq!(
+            ReedSolomonAccumulator::with_domain(shuffled).unwrap_err(),
+            Error::Accumulator(RSError::NotACoset)
+        );
+        let integers: Vec<FieldElement> = (0..8).map(FieldElement::new).collect();
+        assert_eq!(
+            ReedSolomonAccumulator::with_domain(integers).unwrap_err(),
+            Error::Accumulator(RSError::NotACoset)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Subgroup size must divide p - 1")]
+    fn test_multiplicative_domain_size_must_divide_order() {
+        ReedSolomonAccumulator::new_multiplicative(256);
+    }
+
+    #[test]
+    fn test_accumulate_coefficients() {
+        // f(x) = 2x + 1
+        let coeffs = [FieldElement::new(1), FieldElement::new(2)];
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate_coefficients(&coeffs);
+        assert!(acc.verify(&proof));
+
+        for (&x, &y) in acc.domain.iter().zip(&acc.evaluations) {
+            assert_eq!(y, FieldElement::new(2) * x + FieldElement::one());
+        }
+
+        // Same polynomial given by its values on the first domain points
+        let mut by_values = ReedSolomonAccumulator::new();
+        let values = acc.domain[..2]
+            .iter()
+            .map(|&x| FieldElement::new(2) * x + FieldElement::one())
+            .collect();
+        let values_proof = by_values.accumulate(values);
+        assert_eq!(proof.to_bytes(), values_proof.to_bytes());
+    }
+
+    #[test]
+    fn test_accumulate_iter_matches_accumulate() {
+        let state: Vec<FieldElement> = (0..40).map(|i| FieldElement::new(i * i + 7)).collect();
+
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate(state.clone());
+
+        let mut streamed = ReedSolomonAccumulator::new();
+        let streamed_proof = streamed
+            .accumulate_iter((0..40).map(|i| FieldElement::new(i * i + 7)))
+            .unwrap();
+
+        assert_eq!(streamed_proof.to_bytes(), proof.to_bytes());
+        assert_eq!(streamed.evaluations, acc.evaluations);
+        assert!(streamed.verify(&proof));
+
+        // Reusing an accumulator for a second stream works the same way
+        let again = streamed.accumulate_iter(state).unwrap();
+        assert_eq!(again.to_bytes(), proof.to_bytes());
+    }
+
+    #[test]
+    fn test_accumulate_iter_stops_past_bound() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 2);
+        let proof = acc.accumulate_iter((1..=8).map(FieldElement::new)).unwrap();
+        let root = acc.merkle_root;
+
+        // An endless stream is cut off and reported, not collected or truncated
+        assert_eq!(
+            acc.accumulate_iter((0..).map(FieldElement::new)),
+            Err(RSError::StreamTooLong { bound: 8 }.into())
+        );
+        assert_eq!(acc.merkle_root, root);
+        assert!(acc.verify(&proof));
+        assert_eq!(
+            acc.accumulate_iter(std::iter::empty()),
+            Err(RSError::EmptyState.into())
+        );
+    }
+
+    #[test]
+    fn test_over_degree_state_rejected() {
+        let mut acc = ReedSolomonAccumulator::with_params(42, 8, 2);
+        let state: Vec<FieldElement> = (0..9).map(FieldElement::new).collect();
+        assert_eq!(
+            acc.try_accumulate(state).unwrap_err(),
+            Error::Accumulator(RSError::DegreeTooLarge {
+                degree: 9,
+                bound: 8
+            })
+        );
+        assert_eq!(
+            acc.try_accumulate(vec![]).unwrap_err(),
+            Error::Accumulator(RSError::EmptyState)
+        );
+
+        let exact: Vec<FieldElement> = (0..8).map(FieldElement::new).collect();
+        let proof = acc.try_accumulate(exact).unwrap();
+        assert!(acc.verify(&proof));
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the degree bound")]
+    fn test_accumulate_panics_over_degree() {
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.accumulate(
+            (0..=DEFAULT_DEGREE_BOUND as u64)
+                .map(FieldElement::new)
+                .collect(),
+        );
+    }
+
+    #[test]
+    fn test_random_codeword_fails_low_degree_check() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        // Every value is random, so no polynomial within the bound is close
+        let mut rng = StdRng::seed_from_u64(299);
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit((1..=6).map(FieldElement::new).collect());
+        for eval in acc.evaluations.iter_mut() {
+            *eval = FieldElement::new(rng.gen::<u64>());
+        }
+        acc.commit_codeword();
+        let proof = acc.prove();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&proof),
+            Err(VerifyError::LowDegree.into())
+        );
+
+        // Changing only the values past the state still leaves a far codeword
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit((1..=6).map(FieldElement::new).collect());
+        for eval in acc.evaluations.iter_mut().skip(6) {
+            *eval = *eval + FieldElement::new(rng.gen::<u64>());
+        }
+        acc.commit_codeword();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&acc.prove()),
+            Err(VerifyError::LowDegree.into())
+        );
+
+        // FRI checks the openings of the committed codeword, not just its own layers
+        let mut honest = ReedSolomonAccumulator::new();
+        let proof = honest.accumulate((1..=6).map(FieldElement::new).collect());
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+        let mut tampered = proof;
+        tampered.fri_openings[0].0[0] = tampered.fri_openings[0].0[0] + FieldElement::one();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=12).map(FieldElement::new).collect());
+        let bytes = proof.to_bytes();
+        let decoded = RSProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(acc.verify(&decoded));
+
+        assert_eq!(
+            RSProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::Truncated.into())
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            RSProof::from_bytes(&trailing),
+            Err(ProofError::TrailingBytes(1).into())
+        );
+        let mut unreduced = bytes;
+        unreduced[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            RSProof::from_bytes(&unreduced),
+            Err(ProofError::NonCanonical(u64::MAX).into())
+        );
+    }
+
+    #[test]
+    fn test_oversized_length_rejected_before_allocating() {
+        // A bare header claiming four billion challenge evaluations
+        let header = u32::MAX.to_le_bytes();
+        assert_eq!(
+            RSProof::from_bytes(&header),
+            Err(ProofError::TooLarge {
+                declared: u32::MAX as usize,
+                limit: DEFAULT_MAX_ELEMENTS
+            }
+            .into())
+        );
+
+        // Nested lengths are capped too, and the limit is configurable
+        let proof = ReedSolomonAccumulator::new().accumulate(vec![FieldElement::one()]);
+        let bytes = proof.to_bytes();
+        assert!(RSProof::from_bytes_with_limit(&bytes, 64).is_ok());
+        assert!(matches!(
+            RSProof::from_bytes_with_limit(&bytes, 1),
+            Err(Error::Proof(ProofError::TooLarge { limit: 1, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_size_and_cost_estimates() {
+        let state: Vec<FieldElement> = (1..=20).map(FieldElement::new).collect();
+        for num_challenges in [1, 2, 16] {
+            let mut acc = ReedSolomonAccumulator::with_params(462, 64, num_challenges);
+            let proof = acc.accumulate(state.clone());
+            assert_eq!(proof.size_bytes(), proof.to_bytes().len());
+        }
+
+        let few = ReedSolomonAccumulator::with_params(462, 64, 2);
+        let many = ReedSolomonAccumulator::with_params(462, 64, 16);
+        assert!(many.estimated_verify_hashes() > few.estimated_verify_hashes());
+
+        // Same blowup, so as many queries, over deeper trees
+        let larger_domain = ReedSolomonAccumulator::with_params(1386, 192, 2);
+        assert!(larger_domain.estimated_verify_hashes() > few.estimated_verify_hashes());
+    }
+
+    #[test]
+    fn test_proof_size_through_trait() {
+        fn total_bytes<A: Accumulator>(proofs: &[A::Proof]) -> usize {
+            proofs.iter().map(A::proof_size).sum()
+        }
+
+        let mut acc = ReedSolomonAccumulator::new();
+        let proofs = vec![
+            acc.accumulate((1..=4).map(FieldElement::new).collect()),
+            acc.accumulate((1..=12).map(FieldElement::new).collect()),
+        ];
+        let serialized: usize = proofs.iter().map(|p| p.to_bytes().len()).sum();
+        assert_eq!(total_bytes::<ReedSolomonAccumulator>(&proofs), serialized);
+    }
+
+    #[test]
+    fn test_verify_proof_without_state() {
+        let proof = {
+            let mut acc = ReedSolomonAccumulator::new();
+            acc.accumulate((1..=10).map(FieldElement::new).collect())
+        };
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+
+        let mut tampered = proof.clone();
+        tampered.domain_evals[1] = tampered.domain_evals[1] + FieldElement::one();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::InvalidOpening { index: 1 }.into())
+        );
+
+        let mut tampered = proof.clone();
+        tampered.challenge_points[0] = tampered.challenge_points[0] + FieldElement::one();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::ChallengeMismatch.into())
+        );
+
+        // The challenge evaluations are checked through the DEEP quotient,
+        // even though the verifier never sees the state
+        let mut tampered = proof.clone();
+        tampered.challenge_evals[0] = tampered.challenge_evals[0] + FieldElement::one();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::LowDegree.into())
+        );
+
+        // Same FRI proof and openings under another root
+        let mut tampered = proof.clone();
+        tampered.merkle_root[0] ^= 1;
+        assert!(ReedSolomonAccumulator::verify_proof(&tampered).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.merkle_proofs.pop();
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::Malformed.into())
+        );
+
+        let other =
+            ReedSolomonAccumulator::new().accumulate((2..=11).map(FieldElement::new).collect());
+        let mut tampered = proof;
+        tampered.fri = other.fri;
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&tampered),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_verify_opening_at() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let state: Vec<FieldElement> = (1..=4).map(FieldElement::new).collect();
+        let proof = acc.accumulate(state);
+        let root = proof.merkle_root;
+
+        for i in 0..proof.eval_indices.len() {
+            assert!(proof.verify_opening_at(i, &root), "Opening {} failed", i);
+        }
+        assert!(!proof.verify_opening_at(proof.eval_indices.len(), &root));
+
+        // Tampering with one opened value only breaks that opening
+        let mut tampered = proof.clone();
+        tampered.domain_evals[1] = tampered.domain_evals[1] + FieldElement::one();
+        assert!(tampered.verify_opening_at(0, &root));
+        assert!(!tampered.verify_opening_at(1, &root));
+
+        assert!(!proof.verify_opening_at(0, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_proof_id() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate(vec![FieldElement::new(5), FieldElement::new(6)]);
+
+        let copy = proof.clone();
+        assert_eq!(proof.proof_id(), copy.proof_id());
+        assert_eq!(proof.to_bytes(), copy.to_bytes());
+
+        let mut changed = proof.clone();
+        changed.challenge_evals[0] = changed.challenge_evals[0] + FieldElement::one();
+        assert_ne!(proof.proof_id(), changed.proof_id());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_proof_json_round_trip() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate(vec![FieldElement::new(3), FieldElement::new(4)]);
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: RSProof = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.to_bytes(), proof.to_bytes());
+        assert!(acc.verify(&decoded));
+    }
+
+    #[test]
+    fn test_accumulator_large_state() {
+        let mut acc = ReedSolomonAccumulator::new();
+        let state: Vec<FieldElement> = (0..5).map(|i| FieldElement::new(i as u64)).collect();
+        let proof = acc.accumulate(state);
+        assert!(acc.verify(&proof), "Large state verification failed");
+    }
+}
+
+// Proofs a cheating prover would try, built by tampering with honest ones.
+// Each soundness check should reject the attack it targets.
+#[cfg(test)]
+mod adversarial {
+    use super::*;
+
+    // Accumulator committed to the squares 1, 4, ..., n² with the proof
+    // `accumulate` returned for it
+    fn honest(n: u64) -> (ReedSolomonAccumulator, RSProof) {
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate((1..=n).map(|i| FieldElement::new(i * i)).collect());
+        (acc, proof)
+    }
+
+    // Challenge points picked by the prover rather than drawn from the
+    // commitment, answered consistently with the committed polynomial
+    fn chosen_challenges(acc: &ReedSolomonAccumulator, proof: &RSProof) -> RSProof {
+        let mut forged = proof.clone();
+        forged.challenge_points = (0..proof.challenge_points.len())
+            .map(|i| FieldElement::new(1000 + i as u64))
+            .collect();
+        forged.challenge_evals = forged
+            .challenge_points
+            .iter()
+            .map(|&x| acc.evaluate_at(x).unwrap())
+            .collect();
+        forged
+    }
+
+    // Claims the committed polynomial takes `value` at challenge point `i`
+    fn inconsistent_evaluation(proof: &RSProof, i: usize, value: FieldElement) -> RSProof {
+        let mut forged = proof.clone();
+        forged.challenge_evals[i] = value;
+        forged
+    }
+
+    // Opens position `i` to a value the commitment doesn't hold, keeping the path
+    fn inconsistent_opening(proof: &RSProof, i: usize, value: FieldElement) -> RSProof {
+        let mut forged = proof.clone();
+        forged.domain_evals[i] = value;
+        forged
+    }
+
+    // Presents the value and path of opening `from` as opening `to`, at
+    // the position `to` is supposed to open
+    fn reused_path(proof: &RSProof, from: usize, to: usize) -> RSProof {
+        let mut forged = proof.clone();
+        forged.domain_evals[to] = proof.domain_evals[from];
+        forged.merkle_proofs[to] = proof.merkle_proofs[from].clone();
+        forged
+    }
+
+    // Opens positions the prover picked instead of the ones drawn from the
+    // commitment, with genuine values and paths for them
+    fn chosen_indices(acc: &ReedSolomonAccumulator, proof: &RSProof, indices: &[usize]) -> RSProof {
+        let opening = acc.open(indices);
+        let mut forged = proof.clone();
+        forged.eval_indices = opening.indices;
+        forged.domain_evals = opening.values;
+        forged.merkle_proofs = opening.paths;
+        forged
+    }
+
+    // Swaps in the low-degree proof made for another commitment
+    fn foreign_fri(proof: &RSProof, other: &RSProof) -> RSProof {
+        let mut forged = proof.clone();
+        forged.fri = other.fri.clone();
+        forged
+    }
+
+    #[test]
+    fn test_post_hoc_challenges_rejected() {
+        let (acc, proof) = honest(6);
+        let forged = chosen_challenges(&acc, &proof);
+        assert!(!acc.verify(&forged));
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::ChallengeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_evaluations_rejected() {
+        let (acc, proof) = honest(6);
+        let wrong = proof.challenge_evals[0] + FieldElement::one();
+        let forged = inconsistent_evaluation(&proof, 0, wrong);
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::EvaluationMismatch)
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&forged),
+            Err(VerifyError::LowDegree.into())
+        );
+
+        let forged = inconsistent_opening(&proof, 1, FieldElement::new(99));
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::InvalidOpening { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_reused_merkle_paths_rejected() {
+        // The first opening's path is valid, just not for the second position
+        let (acc, proof) = honest(6);
+        let forged = reused_path(&proof, 0, 1);
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::InvalidOpening { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_chosen_indices_rejected() {
+        // The first positions hold the state itself, the easiest to make consistent
+        let (acc, proof) = honest(6);
+        let forged = chosen_indices(&acc, &proof, &[0, 1]);
+        assert!(forged.verify_opening_at(0, &proof.merkle_root));
+        assert!(!acc.verify(&forged));
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::IndexMismatch)
+        );
+        assert_eq!(
+            ReedSolomonAccumulator::verify_proof(&forged),
+            Err(VerifyError::IndexMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_foreign_fri_proof_rejected() {
+        let (acc, proof) = honest(6);
+        let mut other = ReedSolomonAccumulator::new();
+        let other = other.accumulate([5, 3, 8].map(FieldElement::new).to_vec());
+        let forged = foreign_fri(&proof, &other);
+        assert!(!acc.verify(&forged));
+        assert_eq!(
+            acc.verify_against_state(&forged),
+            Err(VerifyError::LowDegree)
+        );
     }
 }
@@ -0,0 +1,22 @@
+// src/consensus/clock.rs
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Source of the current time in seconds since the Unix epoch. Consensus reads
+// time only through this so tests can pin it to a fixed value.
+pub trait Clock: Send + Sync {
+    fn now_secs(&self) -> u64;
+}
+
+// Wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+}
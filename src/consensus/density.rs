@@ -1,88 +1,640 @@
 // src/consensus/density.rs
 
+use super::clock::{Clock, SystemClock};
 use super::Consensus;
-use crate::accumulator::reed_solomon::RSProof;
+use crate::accumulator::reed_solomon::{AggregateProof, ProofError, RSProof, VerifyError};
 use crate::accumulator::{reed_solomon::ReedSolomonAccumulator, Accumulator};
-use crate::crypto::field::FieldElement;
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::crypto::field::{FieldElement, FIELD_PRIME};
+use crate::crypto::transcript::Transcript;
+use crate::error::Error;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
 
 pub const SLOT_DURATION: u64 = 1; // 1 second per slot for demo
 const WINDOW_SIZE: u64 = 50; // Number of blocks to consider for density
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     pub parent_hash: [u8; 32],
     pub height: u64,
     pub timestamp: u64,
     pub state_proof: RSProof,
-    pub accumulator: ReedSolomonAccumulator,
+    // Commitment to the block's state and the state's length. The state and
+    // its codeword stay with the producer; the proof verifies against the root.
+    pub state_root: [u8; 32],
+    pub state_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ChainError {
+    #[error("block {index} does not reference its predecessor")]
+    BrokenLink { index: usize },
+    #[error("block {index} is not one height above its predecessor")]
+    HeightGap { index: usize },
+    #[error("block {index} is not in a later slot than its predecessor")]
+    TimestampRegression { index: usize },
+    #[error("block {index} carries an invalid state proof")]
+    InvalidProof { index: usize },
+    #[error("first block is not at height 0 with an all-zero parent hash")]
+    InvalidGenesis,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("slot duration must be non-zero")]
+    ZeroSlotDuration,
+}
+
+impl Block {
+    // First block of a chain: height 0 at time 0, committing to `state`
+    pub fn genesis(state: Vec<FieldElement>) -> Block {
+        Self::new([0; 32], 0, 0, state)
+    }
+
+    // Block extending this one, linked by hash and one height above it
+    pub fn child(&self, state: Vec<FieldElement>, timestamp: u64) -> Block {
+        Self::new(self.hash(), self.height + 1, timestamp, state)
+    }
+
+    // Commits to `state` with a default accumulator and keeps only the proof
+    // and the commitment
+    pub fn new(
+        parent_hash: [u8; 32],
+        height: u64,
+        timestamp: u64,
+        state: Vec<FieldElement>,
+    ) -> Block {
+        let state_len = state.len();
+        let state_proof = ReedSolomonAccumulator::new().accumulate(state);
+        Block {
+            parent_hash,
+            height,
+            timestamp,
+            state_root: state_proof.merkle_root(),
+            state_len,
+            state_proof,
+        }
+    }
+
+    // The state proof is for this block's root and passes the checks that
+    // need no state (`ReedSolomonAccumulator::verify_proof`)
+    pub fn verify_state_proof(&self) -> bool {
+        self.check_state_proof().is_ok()
+    }
+
+    // `verify_state_proof`, saying which check failed. The proof bounds the
+    // committed state to its degree bound, so a `state_len` of zero or past
+    // it can't be the state's length and is `DegreeOutOfRange`. Within the
+    // bound the length is the producer's claim, which caps the weight a
+    // block can add to `calculate_weighted_density`.
+    pub fn check_state_proof(&self) -> crate::error::Result<()> {
+        if self.state_proof.merkle_root() != self.state_root {
+            return Err(VerifyError::RootMismatch.into());
+        }
+        if self.state_len == 0 || self.state_len > self.state_proof.degree_bound() {
+            return Err(VerifyError::DegreeOutOfRange.into());
+        }
+        ReedSolomonAccumulator::verify_proof(&self.state_proof)
+    }
+
+    // SHA-256 over height, timestamp and state length (little-endian u64),
+    // parent hash and the state root, in that order. The state length feeds
+    // the weighted density, so it is bound to the block like the root is.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.height.to_le_bytes());
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update((self.state_len as u64).to_le_bytes());
+        hasher.update(self.parent_hash);
+        hasher.update(self.state_root);
+        hasher.finalize().into()
+    }
+
+    // Wire encoding: height, timestamp and state length as little-endian
+    // u64, the parent hash, then the state proof's `to_bytes`. The state
+    // root is the proof's root, so it isn't repeated.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(56 + self.state_proof.size_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&(self.state_len as u64).to_le_bytes());
+        out.extend_from_slice(&self.parent_hash);
+        out.extend_from_slice(&self.state_proof.to_bytes());
+        out
+    }
+
+    // Inverse of `to_bytes`. The decoded block verifies its proof and hashes
+    // the same; like any block, it doesn't carry the state itself.
+    pub fn from_bytes(bytes: &[u8]) -> crate::error::Result<Block> {
+        if bytes.len() < 56 {
+            return Err(ProofError::Truncated.into());
+        }
+        let (header, proof) = bytes.split_at(56);
+        let read_u64 =
+            |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+        let state_proof = RSProof::from_bytes(proof)?;
+        Ok(Block {
+            parent_hash: header[24..56].try_into().unwrap(),
+            height: read_u64(0),
+            timestamp: read_u64(8),
+            state_root: state_proof.merkle_root(),
+            state_len: read_u64(16) as usize,
+            state_proof,
+        })
+    }
+}
+
+// Hash of a block's header fields, used as the child's `parent_hash`
+pub fn block_hash(block: &Block) -> [u8; 32] {
+    block.hash()
+}
+
+// Single proof covering every block's state commitment, in chain order.
+// Blocks don't carry their states, so the producer supplies the default
+// accumulator each block was committed with, in the same order; one that
+// doesn't hold its block's commitment makes `verify_aggregate` fail.
+pub fn aggregate_chain_proofs(
+    blocks: &[Block],
+    accumulators: &[ReedSolomonAccumulator],
+) -> AggregateProof {
+    assert_eq!(
+        blocks.len(),
+        accumulators.len(),
+        "Every block needs its accumulator"
+    );
+    let parts: Vec<&ReedSolomonAccumulator> = accumulators.iter().collect();
+    ReedSolomonAccumulator::new().aggregate(&parts)
+}
+
+// Checks that `proof` covers exactly the blocks' state roots, in order, and
+// holds under the default configuration. Each block's state then verifies
+// as its own state proof would under `verify_proof`.
+pub fn verify_aggregate(blocks: &[Block], proof: &AggregateProof) -> crate::error::Result<()> {
+    if proof.roots().len() != blocks.len()
+        || blocks
+            .iter()
+            .zip(proof.roots())
+            .any(|(block, root)| block.state_root != *root)
+    {
+        return Err(VerifyError::RootMismatch.into());
+    }
+    ReedSolomonAccumulator::new().verify_aggregate(proof)
+}
+
+// Two different blocks at the same height, the evidence slashing acts on
+pub fn is_equivocation(a: &Block, b: &Block) -> bool {
+    a.height == b.height && block_hash(a) != block_hash(b)
 }
 
 pub struct DensityConsensus {
     window_size: u64,
     slot_duration: u64,
+    security_param: Option<u64>,
+    max_future_slots: u64,
+    clock: Box<dyn Clock>,
 }
 
-impl DensityConsensus {
-    pub fn new() -> Self {
+// Which rule `choose_fork` applied, with the figures it compared. Ties on
+// those figures go to the chain with the lowest last-block hash.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForkDecision {
+    EmptyChain,
+    LongRange {
+        blocks_after_fork_a: usize,
+        blocks_after_fork_b: usize,
+    },
+    ByLength {
+        len_a: usize,
+        len_b: usize,
+    },
+    ByDensity {
+        density_a: f64,
+        density_b: f64,
+    },
+}
+
+// Collects every `DensityConsensus` option; unset options keep the defaults of `new()`
+pub struct DensityConsensusBuilder {
+    window_size: u64,
+    slot_duration: u64,
+    security_param: Option<u64>,
+    max_future_slots: u64,
+    clock: Option<Box<dyn Clock>>,
+}
+
+impl Default for DensityConsensusBuilder {
+    fn default() -> Self {
         Self {
             window_size: WINDOW_SIZE,
             slot_duration: SLOT_DURATION,
+            security_param: None,
+            max_future_slots: 0,
+            clock: None,
+        }
+    }
+}
+
+impl DensityConsensusBuilder {
+    pub fn window_size(self, window_size: u64) -> Self {
+        Self {
+            window_size,
+            ..self
+        }
+    }
+
+    pub fn slot_duration(self, slot_duration: u64) -> Self {
+        Self {
+            slot_duration,
+            ..self
+        }
+    }
+
+    pub fn security_param(self, k: u64) -> Self {
+        Self {
+            security_param: Some(k),
+            ..self
+        }
+    }
+
+    pub fn max_future_slots(self, slots: u64) -> Self {
+        Self {
+            max_future_slots: slots,
+            ..self
+        }
+    }
+
+    pub fn clock(self, clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Some(Box::new(clock)),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> crate::error::Result<DensityConsensus> {
+        if self.slot_duration == 0 {
+            return Err(ConfigError::ZeroSlotDuration.into());
+        }
+        Ok(DensityConsensus {
+            window_size: self.window_size,
+            slot_duration: self.slot_duration,
+            security_param: self.security_param,
+            max_future_slots: self.max_future_slots,
+            clock: self.clock.unwrap_or_else(|| Box::new(SystemClock)),
+        })
+    }
+}
+
+impl DensityConsensus {
+    pub fn builder() -> DensityConsensusBuilder {
+        DensityConsensusBuilder::default()
+    }
+
+    pub fn new() -> Self {
+        Self::with_config(WINDOW_SIZE, SLOT_DURATION)
+    }
+
+    pub fn with_config(window_size: u64, slot_duration: u64) -> Self {
+        assert!(slot_duration > 0, "Slot duration must be non-zero");
+        Self {
+            window_size,
+            slot_duration,
+            security_param: None,
+            max_future_slots: 0,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    // Replaces the wall clock used for the current slot
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            ..self
+        }
+    }
+
+    // Accept blocks up to `slots` ahead of the current slot, to tolerate
+    // clock skew between peers
+    pub fn with_max_future_slots(self, slots: u64) -> Self {
+        Self {
+            max_future_slots: slots,
+            ..self
+        }
+    }
+
+    // Forks that diverge more than `k` blocks back are decided by density in
+    // the window right after the fork point (Ouroboros Genesis style), so a
+    // fabricated long-range fork can't win on length or late density alone
+    pub fn with_security_param(k: u64) -> Self {
+        Self {
+            security_param: Some(k),
+            ..Self::new()
         }
     }
 
     // Make helper methods public
+    // Zero for an inverted range rather than an underflow
     pub fn expected_slots(&self, start_time: u64, end_time: u64) -> u64 {
-        (end_time - start_time) / self.slot_duration
+        end_time.saturating_sub(start_time) / self.slot_duration
     }
 
     pub fn current_slot(&self) -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            / self.slot_duration
+        self.slot_of(self.clock.now_secs())
+    }
+
+    // Slot a timestamp (in seconds) falls into
+    pub fn slot_of(&self, timestamp: u64) -> u64 {
+        timestamp / self.slot_duration
     }
 
     pub fn window_density(&self, blocks: &[Block], start_slot: u64, end_slot: u64) -> f64 {
-        let blocks_in_window: Vec<&Block> = blocks
+        self.weighted_window_density(blocks, start_slot, end_slot, &|_| 1.0)
+    }
+
+    // Blocks with timestamps in `[start_secs, end_secs]` per slot in that
+    // range, counting at least one slot as `window_density` does. An
+    // inverted range is empty and has density zero.
+    pub fn density_in_range(&self, blocks: &[Block], start_secs: u64, end_secs: u64) -> f64 {
+        if start_secs > end_secs {
+            return 0.0;
+        }
+        let count = blocks
+            .iter()
+            .filter(|b| (start_secs..=end_secs).contains(&b.timestamp))
+            .count();
+        count as f64 / self.expected_slots(start_secs, end_secs).max(1) as f64
+    }
+
+    // Like `calculate_density`, but each block counts for the length of the
+    // state it committed to, so denser work beats denser count
+    pub fn calculate_weighted_density(&self, blocks: &[Block]) -> f64 {
+        self.average_density(blocks, &|b| b.state_len as f64)
+    }
+
+    fn weighted_window_density(
+        &self,
+        blocks: &[Block],
+        start_slot: u64,
+        end_slot: u64,
+        weight: &impl Fn(&Block) -> f64,
+    ) -> f64 {
+        let window_weight: f64 = blocks
             .iter()
             .filter(|b| {
-                let block_slot = b.timestamp / self.slot_duration;
+                let block_slot = self.slot_of(b.timestamp);
                 block_slot >= start_slot && block_slot <= end_slot
             })
-            .collect();
+            .map(weight)
+            .sum();
 
-        let expected_blocks = self.expected_slots(
-            start_slot * self.slot_duration,
-            end_slot * self.slot_duration,
-        );
+        // A window whose first and last block share a slot still covers one
+        // slot, so count at least one to keep the density finite
+        let expected_blocks = self
+            .expected_slots(
+                start_slot * self.slot_duration,
+                end_slot * self.slot_duration,
+            )
+            .max(1);
 
-        blocks_in_window.len() as f64 / expected_blocks as f64
+        window_weight / expected_blocks as f64
     }
-}
 
-impl Consensus for DensityConsensus {
-    type Block = Block;
-    type State = Vec<FieldElement>;
+    // Density of each sliding window in order, one window starting at every
+    // block and spanning up to `window_size` blocks after it. Their average
+    // is `calculate_density`; a dip shows where the chain went sparse.
+    // Windows whose last block is in an earlier slot than their first
+    // (unsorted input) have no meaningful density and are skipped.
+    pub fn window_densities(&self, blocks: &[Block]) -> Vec<f64> {
+        self.weighted_window_densities(blocks, &|_| 1.0)
+    }
+
+    fn weighted_window_densities(
+        &self,
+        blocks: &[Block],
+        weight: &impl Fn(&Block) -> f64,
+    ) -> Vec<f64> {
+        (0..blocks.len())
+            .filter_map(|i| {
+                let end_idx = (i as u64 + self.window_size).min(blocks.len() as u64 - 1) as usize;
+                let start_slot = self.slot_of(blocks[i].timestamp);
+                let end_slot = self.slot_of(blocks[end_idx].timestamp);
+                (start_slot <= end_slot).then(|| {
+                    self.weighted_window_density(&blocks[i..=end_idx], start_slot, end_slot, weight)
+                })
+            })
+            .collect()
+    }
+
+    // Average of the weighted density over windows starting at each block
+    fn average_density(&self, blocks: &[Block], weight: &impl Fn(&Block) -> f64) -> f64 {
+        let densities = self.weighted_window_densities(blocks, weight);
+        if densities.is_empty() {
+            return 0.0;
+        }
+        densities.iter().sum::<f64>() / densities.len() as f64
+    }
+
+    // Whether a party holding `stake` of `total_stake` may produce the block
+    // for `slot`. The VRF output is hashed with the slot into a uniform field
+    // element, which wins if it falls below the stake-proportional share of
+    // the field, so eligibility only grows with stake.
+    pub fn is_slot_leader(
+        &self,
+        slot: u64,
+        stake: u64,
+        total_stake: u64,
+        vrf_output: &[u8],
+    ) -> bool {
+        assert!(total_stake > 0, "Total stake must be non-zero");
+        assert!(stake <= total_stake, "Stake cannot exceed total stake");
+
+        let mut transcript = Transcript::new(b"endgame-slot-leader");
+        transcript.absorb(&slot.to_le_bytes());
+        transcript.absorb(vrf_output);
+        let draw: FieldElement = transcript.challenge_field();
+
+        let threshold = stake as u128 * FIELD_PRIME as u128 / total_stake as u128;
+        (draw.value() as u128) < threshold
+    }
+
+    // Blocks per slot over the last `window_slots` slots up to the tip. A
+    // window reaching back past the first block is clamped to start there.
+    pub fn chain_growth(&self, blocks: &[Block], window_slots: u64) -> f64 {
+        let (Some(first), Some(tip)) = (blocks.first(), blocks.last()) else {
+            return 0.0;
+        };
+        let end_slot = self.slot_of(tip.timestamp);
+        let start_slot = end_slot
+            .saturating_sub(window_slots)
+            .max(self.slot_of(first.timestamp));
+        if end_slot <= start_slot {
+            return 0.0;
+        }
+
+        let grown = blocks
+            .iter()
+            .filter(|b| {
+                let slot = self.slot_of(b.timestamp);
+                slot > start_slot && slot <= end_slot
+            })
+            .count();
+        grown as f64 / (end_slot - start_slot) as f64
+    }
+
+    // Whether the chain produced at least `min_density` blocks per slot over
+    // its last `window_slots` slots
+    pub fn meets_density_threshold(
+        &self,
+        blocks: &[Block],
+        min_density: f64,
+        window_slots: u64,
+    ) -> bool {
+        self.chain_growth(blocks, window_slots) >= min_density
+    }
+
+    // Density after collapsing each slot to its first block, so a producer
+    // flooding a slot with equivocating blocks can't inflate the measurement
+    pub fn density_ignoring_equivocation(&self, blocks: &[Block]) -> f64 {
+        self.calculate_density(&self.canonical_blocks(blocks))
+    }
 
-    fn validate_block(&self, block: &Self::Block, state: &Self::State) -> bool {
-        // Validate timestamp
-        let current_slot = self.current_slot();
-        let block_slot = block.timestamp / self.slot_duration;
-        if block_slot > current_slot {
+    // Whether two of the blocks fall in the same slot, in any order. On a
+    // linked chain `validate_chain` already rejects this, since slots must
+    // strictly increase from parent to child.
+    pub fn has_slot_collisions(&self, blocks: &[Block]) -> bool {
+        let mut seen_slots = HashSet::new();
+        !blocks
+            .iter()
+            .all(|b| seen_slots.insert(self.slot_of(b.timestamp)))
+    }
+
+    // Full validation of a block extending `parent`: it must reference the
+    // parent's hash, sit exactly one height above it, come in a later slot
+    // and pass `check_block`
+    pub fn validate_block_with_parent(&self, block: &Block, parent: &Block) -> bool {
+        if block.parent_hash != block_hash(parent) {
+            return false;
+        }
+        if parent.height.checked_add(1) != Some(block.height) {
+            return false;
+        }
+        if !self.is_later_slot(block, parent) {
             return false;
         }
+        self.check_block(block).is_ok()
+    }
 
-        // Validate state proof
-        block.accumulator.verify(&block.state_proof)
+    // Each slot has at most one block on a chain, so slots strictly increase
+    // from parent to child. This rejects both backward timestamps and a
+    // timestamp equal to the parent's.
+    fn is_later_slot(&self, block: &Block, parent: &Block) -> bool {
+        self.slot_of(block.timestamp) > self.slot_of(parent.timestamp)
     }
 
-    fn choose_fork<'a>(
+    // A genesis block has no parent: it sits at height 0, references the
+    // all-zero hash and carries a valid state proof
+    pub fn validate_genesis(&self, block: &Block) -> bool {
+        self.check_genesis(block).is_ok()
+    }
+
+    // `validate_genesis`, saying why a block was rejected: `InvalidGenesis`
+    // for the header, or the state proof's failure
+    pub fn check_genesis(&self, block: &Block) -> crate::error::Result<()> {
+        if !Self::is_genesis_header(block) {
+            return Err(ChainError::InvalidGenesis.into());
+        }
+        block.check_state_proof()
+    }
+
+    fn is_genesis_header(block: &Block) -> bool {
+        block.height == 0 && block.parent_hash == [0u8; 32]
+    }
+
+    // `validate_block`, saying why a block was rejected: a slot too far
+    // past the clock, or a state proof that fails `check_state_proof`
+    pub fn check_block(&self, block: &Block) -> crate::error::Result<()> {
+        let latest = self.current_slot().saturating_add(self.max_future_slots);
+        let slot = self.slot_of(block.timestamp);
+        if slot > latest {
+            return Err(Error::FutureBlock { slot, latest });
+        }
+        block.check_state_proof()
+    }
+
+    // Checks the chain's internal consistency: it starts from a genesis
+    // block, every later block links to and sits one height above its
+    // predecessor, slots strictly increase, and each state proof verifies
+    // against the block's accumulator
+    pub fn validate_chain(&self, chain: &[Block]) -> crate::error::Result<()> {
+        for (index, block) in chain.iter().enumerate() {
+            if index == 0 {
+                if !Self::is_genesis_header(block) {
+                    return Err(ChainError::InvalidGenesis.into());
+                }
+            } else {
+                let parent = &chain[index - 1];
+                if block.parent_hash != parent.hash() {
+                    return Err(ChainError::BrokenLink { index }.into());
+                }
+                if parent.height.checked_add(1) != Some(block.height) {
+                    return Err(ChainError::HeightGap { index }.into());
+                }
+                if !self.is_later_slot(block, parent) {
+                    return Err(ChainError::TimestampRegression { index }.into());
+                }
+            }
+            if !block.verify_state_proof() {
+                return Err(ChainError::InvalidProof { index }.into());
+            }
+        }
+        Ok(())
+    }
+
+    // Number of leading blocks the two chains have in common
+    fn common_prefix_len(chain_a: &[Block], chain_b: &[Block]) -> usize {
+        chain_a
+            .iter()
+            .zip(chain_b)
+            .take_while(|(a, b)| a.hash() == b.hash())
+            .count()
+    }
+
+    // Index of the last block the two chains share, or `None` when they
+    // don't even agree on genesis
+    pub fn fork_point(chain_a: &[Block], chain_b: &[Block]) -> Option<usize> {
+        Self::common_prefix_len(chain_a, chain_b).checked_sub(1)
+    }
+
+    // Blocks of a fork that fall in the `window_size` slots after the fork point
+    fn blocks_after_fork(&self, fork: &[Block], fork_slot: u64) -> usize {
+        let end_slot = fork_slot.saturating_add(self.window_size);
+        fork.iter()
+            .filter(|b| {
+                let slot = self.slot_of(b.timestamp);
+                slot > fork_slot && slot <= end_slot
+            })
+            .count()
+    }
+
+    // `choose_fork`, also reporting which rule decided and the figures it compared
+    pub fn choose_fork_explained<'a>(
         &self,
-        chain_a: &'a [Self::Block],
-        chain_b: &'a [Self::Block],
-    ) -> &'a [Self::Block] {
+        chain_a: &'a [Block],
+        chain_b: &'a [Block],
+    ) -> (&'a [Block], ForkDecision) {
+        // A syncing peer may have nothing yet: any chain beats an empty one,
+        // and with two empty chains the first argument is returned
+        match (chain_a.is_empty(), chain_b.is_empty()) {
+            (_, true) => return (chain_a, ForkDecision::EmptyChain),
+            (true, false) => return (chain_b, ForkDecision::EmptyChain),
+            _ => {}
+        }
+
+        if let Some(choice) = self.long_range_choice(chain_a, chain_b) {
+            return choice;
+        }
+
         // For recent forks (within window_size), use simple length comparison
         if chain_a
             .last()
@@ -91,45 +643,1065 @@ impl Consensus for DensityConsensus {
             .abs_diff(chain_b.last().unwrap().timestamp)
             < self.window_size * self.slot_duration
         {
-            return if chain_a.len() > chain_b.len() {
-                chain_a
-            } else {
-                chain_b
+            let chain = match chain_a.len().cmp(&chain_b.len()) {
+                Ordering::Greater => chain_a,
+                Ordering::Less => chain_b,
+                Ordering::Equal => Self::tie_break(chain_a, chain_b),
+            };
+            let decision = ForkDecision::ByLength {
+                len_a: chain_a.len(),
+                len_b: chain_b.len(),
             };
+            return (chain, decision);
         }
 
         // For older forks, use density-based selection
         let density_a = self.calculate_density(chain_a);
         let density_b = self.calculate_density(chain_b);
 
-        if density_a > density_b {
+        let chain = if density_a > density_b {
             chain_a
+        } else if density_b > density_a {
+            chain_b
         } else {
+            Self::tie_break(chain_a, chain_b)
+        };
+        (
+            chain,
+            ForkDecision::ByDensity {
+                density_a,
+                density_b,
+            },
+        )
+    }
+
+    // Returns the preferred chain when the long-range rule applies
+    fn long_range_choice<'a>(
+        &self,
+        chain_a: &'a [Block],
+        chain_b: &'a [Block],
+    ) -> Option<(&'a [Block], ForkDecision)> {
+        let k = self.security_param?;
+        let prefix = Self::common_prefix_len(chain_a, chain_b);
+        let (fork_a, fork_b) = (&chain_a[prefix..], &chain_b[prefix..]);
+        if fork_a.len().max(fork_b.len()) as u64 <= k {
+            return None;
+        }
+
+        let fork_slot = match prefix {
+            0 => 0,
+            n => self.slot_of(chain_a[n - 1].timestamp),
+        };
+        let count_a = self.blocks_after_fork(fork_a, fork_slot);
+        let count_b = self.blocks_after_fork(fork_b, fork_slot);
+        let chain = match count_a.cmp(&count_b) {
+            Ordering::Greater => chain_a,
+            Ordering::Less => chain_b,
+            Ordering::Equal => Self::tie_break(chain_a, chain_b),
+        };
+        let decision = ForkDecision::LongRange {
+            blocks_after_fork_a: count_a,
+            blocks_after_fork_b: count_b,
+        };
+        Some((chain, decision))
+    }
+
+    // Canonical choice between equally good non-empty chains: the one whose
+    // last block hash is lexicographically lowest wins, whatever the argument order
+    fn tie_break<'a>(chain_a: &'a [Block], chain_b: &'a [Block]) -> &'a [Block] {
+        let hash_a = chain_a.last().map(Block::hash);
+        let hash_b = chain_b.last().map(Block::hash);
+        if hash_b < hash_a {
             chain_b
+        } else {
+            chain_a
+        }
+    }
+
+    fn canonical_blocks(&self, blocks: &[Block]) -> Vec<Block> {
+        let mut seen_slots = HashSet::new();
+        blocks
+            .iter()
+            .filter(|b| seen_slots.insert(self.slot_of(b.timestamp)))
+            .cloned()
+            .collect()
+    }
+}
+
+// Rolling `calculate_density` over the most recent blocks of a chain, for
+// callers that extend a chain one block at a time. It keeps the slots of the
+// last `window_size + 1` blocks, enough for one full window, so pushing is
+// O(1) and reading the density is O(window). Blocks must arrive in slot
+// order, as they do on a valid chain.
+#[derive(Debug, Clone)]
+pub struct DensityTracker {
+    window_size: u64,
+    slot_duration: u64,
+    slots: VecDeque<u64>,
+}
+
+impl DensityTracker {
+    pub fn new(consensus: &DensityConsensus) -> Self {
+        Self {
+            window_size: consensus.window_size,
+            slot_duration: consensus.slot_duration,
+            slots: VecDeque::new(),
         }
     }
 
+    pub fn push_block(&mut self, block: &Block) {
+        let slot = block.timestamp / self.slot_duration;
+        assert!(
+            self.slots.back().is_none_or(|&last| last <= slot),
+            "Blocks must be pushed in slot order"
+        );
+        if self.slots.len() as u64 > self.window_size {
+            self.slots.pop_front();
+        }
+        self.slots.push_back(slot);
+    }
+
+    // Equal to `calculate_density` over the blocks still in the window. Every
+    // window then ends at the newest block, and with slots in order each one
+    // holds all blocks from its start onwards.
+    pub fn current_density(&self) -> f64 {
+        let Some(&last) = self.slots.back() else {
+            return 0.0;
+        };
+        let len = self.slots.len();
+        let total: f64 = self
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(i, &slot)| {
+                let expected = (last - slot).max(1);
+                (len - i) as f64 / expected as f64
+            })
+            .sum();
+        total / len as f64
+    }
+
+    // Number of blocks currently in the window
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+impl Default for DensityConsensus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Consensus for DensityConsensus {
+    type Block = Block;
+    type State = Vec<FieldElement>;
+
+    fn validate_block(&self, block: &Self::Block, _state: &Self::State) -> bool {
+        self.check_block(block).is_ok()
+    }
+
+    fn choose_fork<'a>(
+        &self,
+        chain_a: &'a [Self::Block],
+        chain_b: &'a [Self::Block],
+    ) -> &'a [Self::Block] {
+        self.choose_fork_explained(chain_a, chain_b).0
+    }
+
     fn calculate_density(&self, blocks: &[Self::Block]) -> f64 {
-        let num_windows = (blocks.len() as u64).max(1);
-        let window_size = self.window_size;
+        self.average_density(blocks, &|_| 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    struct MockClock(u64);
+
+    impl Clock for MockClock {
+        fn now_secs(&self) -> u64 {
+            self.0
+        }
+    }
+
+    fn create_block(height: u64, timestamp: u64) -> Block {
+        Block::new([0; 32], height, timestamp, vec![FieldElement::new(height)])
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_block_json_round_trip() {
+        let consensus = DensityConsensus::new();
+        let block = create_block(4, 4 * SLOT_DURATION);
+
+        let json = serde_json::to_string(&block).unwrap();
+        let decoded: Block = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.height, block.height);
+        assert_eq!(decoded.timestamp, block.timestamp);
+        assert_eq!(decoded.state_proof.to_bytes(), block.state_proof.to_bytes());
+        assert!(consensus.validate_block(&decoded, &vec![]));
+    }
+
+    fn create_child(parent: &Block, timestamp: u64) -> Block {
+        Block {
+            parent_hash: block_hash(parent),
+            ..create_block(parent.height + 1, timestamp)
+        }
+    }
+
+    #[test]
+    fn test_custom_config_changes_slots() {
+        let default = DensityConsensus::new();
+        let six_second = DensityConsensus::with_config(2160, 6);
+
+        // Timestamp 12 is slot 12 with 1s slots but slot 2 with 6s slots
+        let block = create_block(1, 12);
+        assert_eq!(
+            default.window_density(std::slice::from_ref(&block), 2, 3),
+            0.0
+        );
+        assert_eq!(
+            six_second.window_density(std::slice::from_ref(&block), 2, 3),
+            1.0
+        );
+
+        assert_eq!(six_second.expected_slots(0, 60), 10);
+        assert!(six_second.current_slot() < default.current_slot());
+    }
+
+    #[test]
+    fn test_slot_of_matches_current_slot() {
+        for consensus in [
+            DensityConsensus::new(),
+            DensityConsensus::with_config(50, 6),
+        ] {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let slot = consensus.slot_of(now);
+            let current = consensus.current_slot();
+            assert!(current == slot || current == slot + 1);
+        }
+
+        let consensus = DensityConsensus::with_config(50, 6);
+        assert_eq!(consensus.slot_of(0), 0);
+        assert_eq!(consensus.slot_of(5), 0);
+        assert_eq!(consensus.slot_of(6), 1);
+    }
+
+    #[test]
+    fn test_future_blocks_rejected_with_mock_clock() {
+        let now = 600;
+        let consensus = DensityConsensus::with_config(WINDOW_SIZE, 6).with_clock(MockClock(now));
+        assert_eq!(consensus.current_slot(), 100);
+
+        // Last second of the current slot is fine, first second of the next is not
+        let current = create_block(1, now + 5);
+        let ahead = create_block(1, now + 6);
+        assert!(consensus.validate_block(&current, &vec![]));
+        assert!(!consensus.validate_block(&ahead, &vec![]));
+    }
+
+    #[test]
+    fn test_future_slot_tolerance() {
+        let now = 600;
+        let consensus = DensityConsensus::with_config(WINDOW_SIZE, 6)
+            .with_clock(MockClock(now))
+            .with_max_future_slots(2);
+
+        // Slot 102 is exactly two ahead, slot 103 one beyond the tolerance
+        let at_limit = create_block(1, now + 2 * 6 + 5);
+        let beyond = create_block(1, now + 3 * 6);
+        assert!(consensus.validate_block(&at_limit, &vec![]));
+        assert!(!consensus.validate_block(&beyond, &vec![]));
+    }
+
+    #[test]
+    fn test_builder_applies_every_option() {
+        let now = 600;
+        let consensus = DensityConsensus::builder()
+            .window_size(10)
+            .slot_duration(6)
+            .security_param(3)
+            .max_future_slots(1)
+            .clock(MockClock(now))
+            .build()
+            .unwrap();
+
+        assert_eq!(consensus.window_size, 10);
+        assert_eq!(consensus.security_param, Some(3));
+        assert_eq!(consensus.slot_of(59), 9);
+        assert_eq!(consensus.current_slot(), 100);
+        assert!(consensus.validate_block(&create_block(1, now + 11), &vec![]));
+        assert!(!consensus.validate_block(&create_block(1, now + 12), &vec![]));
+
+        let defaults = DensityConsensus::builder().build().unwrap();
+        assert_eq!(defaults.window_size, WINDOW_SIZE);
+        assert_eq!(defaults.slot_duration, SLOT_DURATION);
+        assert_eq!(defaults.security_param, None);
+        assert_eq!(defaults.max_future_slots, 0);
+
+        assert_eq!(
+            DensityConsensus::builder().slot_duration(0).build().err(),
+            Some(Error::Config(ConfigError::ZeroSlotDuration))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Slot duration must be non-zero")]
+    fn test_zero_slot_duration_rejected() {
+        DensityConsensus::with_config(WINDOW_SIZE, 0);
+    }
+
+    #[test]
+    fn test_block_hash_is_deterministic() {
+        let block = create_block(3, 3 * SLOT_DURATION);
+        assert_eq!(block.hash(), block.clone().hash());
+        assert_eq!(block.hash(), block_hash(&block));
+
+        let mut other = block.clone();
+        other.height += 1;
+        assert_ne!(other.hash(), block.hash());
+
+        let mut other = block.clone();
+        other.timestamp += 1;
+        assert_ne!(other.hash(), block.hash());
+
+        let mut other = block.clone();
+        other.parent_hash = [1; 32];
+        assert_ne!(other.hash(), block.hash());
+
+        let mut other = block.clone();
+        other.state_len += 1;
+        assert_ne!(other.hash(), block.hash());
+
+        // Different state commits to a different Merkle root
+        let other = Block {
+            state_root: create_block(4, 0).state_root,
+            ..block.clone()
+        };
+        assert_ne!(other.hash(), block.hash());
+    }
+
+    #[test]
+    fn test_parent_linkage() {
+        let consensus = DensityConsensus::new();
+        let genesis = create_block(0, 0);
+        let child = create_child(&genesis, SLOT_DURATION);
+
+        assert!(consensus.validate_block_with_parent(&child, &genesis));
+
+        // Points at some other block
+        let stranger = create_block(0, 5 * SLOT_DURATION);
+        assert!(!consensus.validate_block_with_parent(&child, &stranger));
+        let mut unlinked = child.clone();
+        unlinked.parent_hash = [0xab; 32];
+        assert!(!consensus.validate_block_with_parent(&unlinked, &genesis));
+
+        // Timestamped before, or in the same slot as, the parent
+        let parent = create_block(1, 10 * SLOT_DURATION);
+        assert!(consensus
+            .validate_block_with_parent(&create_child(&parent, 11 * SLOT_DURATION), &parent));
+        let backward = create_child(&parent, 9 * SLOT_DURATION);
+        assert!(!consensus.validate_block_with_parent(&backward, &parent));
+        let same_time = create_child(&parent, 10 * SLOT_DURATION);
+        assert!(!consensus.validate_block_with_parent(&same_time, &parent));
+
+        // Correct parent hash but skips a height
+        let mut skipping = create_child(&genesis, SLOT_DURATION);
+        skipping.height = 2;
+        assert!(!consensus.validate_block_with_parent(&skipping, &genesis));
+    }
+
+    fn create_chain(len: u64) -> Vec<Block> {
+        let mut chain = vec![create_block(0, 0)];
+        for i in 1..len {
+            let child = create_child(&chain[i as usize - 1], i * SLOT_DURATION);
+            chain.push(child);
+        }
+        chain
+    }
+
+    #[test]
+    fn test_block_keeps_only_the_commitment() {
+        let consensus = DensityConsensus::new();
+        let block = Block::genesis((1..=10).map(FieldElement::new).collect());
+        assert!(consensus.validate_genesis(&block));
+        assert!(consensus.validate_block(&block, &vec![]));
+        assert_eq!(block.state_len, 10);
+
+        // Previously the header sat next to the proof and the producer's
+        // accumulator, whose 462-point domain and codeword lived on the heap.
+        // The proof is in both, so dropping the accumulator for the root and
+        // length must save at least those two vectors.
+        let mut acc = ReedSolomonAccumulator::new();
+        acc.commit((1..=10).map(FieldElement::new).collect());
+        let accumulator_heap = 2 * acc.domain_size() * std::mem::size_of::<FieldElement>();
+        let old_size =
+            48 + std::mem::size_of::<RSProof>() + std::mem::size_of_val(&acc) + accumulator_heap;
+        assert!(old_size - std::mem::size_of_val(&block) >= accumulator_heap);
+        assert!(accumulator_heap >= 2 * 462 * 8);
+
+        let mut wrong_root = block.clone();
+        wrong_root.state_root = [1; 32];
+        assert!(!wrong_root.verify_state_proof());
+        assert!(!consensus.validate_genesis(&wrong_root));
+    }
+
+    #[test]
+    fn test_block_wire_round_trip() {
+        let consensus = DensityConsensus::new();
+        let genesis = Block::genesis(vec![FieldElement::new(1)]);
+        let block = genesis.child((1..=6).map(FieldElement::new).collect(), 1);
+
+        let bytes = block.to_bytes();
+        let decoded = Block::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.hash(), block.hash());
+        assert_eq!(decoded.state_proof, block.state_proof);
+        assert!(consensus.validate_block(&decoded, &vec![]));
+        assert!(consensus.validate_block_with_parent(&decoded, &genesis));
+
+        assert!(matches!(
+            Block::from_bytes(&bytes[..40]),
+            Err(Error::Proof(ProofError::Truncated))
+        ));
+        assert!(Block::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_density_in_range() {
+        let consensus = DensityConsensus::with_config(50, 2);
+        // Blocks at 0, 2, 4, 10, 12 and 20 seconds
+        let chain: Vec<Block> = [0, 2, 4, 10, 12, 20]
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| create_block(i as u64, t))
+            .collect();
+
+        // 4..=12 holds the blocks at 4, 10 and 12 over 8 / 2 = 4 slots
+        assert_eq!(consensus.density_in_range(&chain, 4, 12), 0.75);
+        // All six blocks over 20 / 2 = 10 slots
+        assert_eq!(consensus.density_in_range(&chain, 0, 20), 0.6);
+        assert_eq!(consensus.density_in_range(&chain, 12, 12), 1.0);
+
+        let later: Vec<Block> = chain.iter().skip(3).cloned().collect();
+        assert_eq!(consensus.density_in_range(&later, 0, 8), 0.0);
+        assert_eq!(consensus.density_in_range(&chain, 12, 4), 0.0);
+    }
+
+    #[test]
+    fn test_aggregate_chain_proofs() {
+        let chain = create_chain(10);
+        // The producer's accumulators, committed to the same states as `create_block`
+        let accumulators: Vec<ReedSolomonAccumulator> = (0..10)
+            .map(|height| {
+                let mut acc = ReedSolomonAccumulator::new();
+                acc.commit(vec![FieldElement::new(height)]);
+                acc
+            })
+            .collect();
+        let aggregate = aggregate_chain_proofs(&chain, &accumulators);
+        assert_eq!(verify_aggregate(&chain, &aggregate), Ok(()));
+        let roots: Vec<[u8; 32]> = chain.iter().map(|b| b.state_root).collect();
+        assert_eq!(aggregate.roots(), roots.as_slice());
+
+        // The aggregate only speaks for the blocks whose roots it covers
+        let mut tampered = chain.clone();
+        tampered[4].state_root = chain[5].state_root;
+        assert_eq!(
+            verify_aggregate(&tampered, &aggregate),
+            Err(VerifyError::RootMismatch.into())
+        );
+        assert_eq!(
+            verify_aggregate(&chain[..9], &aggregate),
+            Err(VerifyError::RootMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_aggregate_rejects_non_codeword_block() {
+        let mut chain = create_chain(4);
+        let mut accumulators: Vec<ReedSolomonAccumulator> = (0..4)
+            .map(|height| {
+                let mut acc = ReedSolomonAccumulator::new();
+                acc.commit(vec![FieldElement::new(height)]);
+                acc
+            })
+            .collect();
+
+        // Block 2 commits 200 values over the default domain: a codeword of
+        // a wider code, far from any of the default degree bound
+        let mut wide = ReedSolomonAccumulator::with_params(462, 200, 2);
+        let root = wide.commit((1..=200).map(FieldElement::new).collect());
+        chain[2].state_root = root;
+        chain[2].state_proof = wide.prove();
+        chain[2].state_len = 200;
+        accumulators[2] = wide;
+
+        let aggregate = aggregate_chain_proofs(&chain, &accumulators);
+        assert_eq!(
+            verify_aggregate(&chain, &aggregate),
+            Err(VerifyError::LowDegree.into())
+        );
+    }
+
+    #[test]
+    fn test_is_equivocation() {
+        let block = create_block(3, 10);
+        let rival = create_block(3, 11);
+        assert!(is_equivocation(&block, &rival));
+        assert!(!is_equivocation(&block, &block));
+        assert!(!is_equivocation(&block, &create_block(4, 11)));
+    }
+
+    #[test]
+    fn test_has_slot_collisions() {
+        let consensus = DensityConsensus::with_config(50, 6);
+        let chain: Vec<Block> = (0..5).map(|i| create_block(i, i * 6)).collect();
+        assert!(!consensus.has_slot_collisions(&chain));
+
+        // Timestamps 12 and 17 are both slot 2
+        let mut colliding = chain.clone();
+        colliding.push(create_block(5, 17));
+        assert!(consensus.has_slot_collisions(&colliding));
+        assert!(!consensus.has_slot_collisions(&[]));
+    }
+
+    #[test]
+    fn test_genesis_and_child_build_linked_chain() {
+        let consensus = DensityConsensus::new();
+        let mut chain = vec![Block::genesis(vec![FieldElement::new(1)])];
+        for i in 1..5u64 {
+            let state = (0..=i).map(FieldElement::new).collect();
+            let child = chain.last().unwrap().child(state, i * SLOT_DURATION);
+            chain.push(child);
+        }
+
+        assert_eq!(consensus.validate_chain(&chain), Ok(()));
+        assert_eq!(chain[0].height, 0);
+        assert_eq!(chain[0].parent_hash, [0; 32]);
+        assert_eq!(chain[4].height, 4);
+        assert_eq!(chain[4].parent_hash, block_hash(&chain[3]));
+        assert_eq!(chain[4].state_len, 5);
+    }
+
+    #[test]
+    fn test_validate_genesis() {
+        let consensus = DensityConsensus::new();
+        let genesis = Block::genesis(vec![FieldElement::new(1)]);
+        assert!(consensus.validate_genesis(&genesis));
+
+        let mut with_parent = genesis.clone();
+        with_parent.parent_hash = [1; 32];
+        assert!(!consensus.validate_genesis(&with_parent));
+
+        let raised = Block {
+            height: 1,
+            ..genesis.clone()
+        };
+        assert!(!consensus.validate_genesis(&raised));
+
+        let mut bad_proof = genesis.clone();
+        bad_proof.state_proof = create_block(5, 0).state_proof;
+        assert!(!consensus.validate_genesis(&bad_proof));
+
+        // validate_chain applies the genesis rules to the first block
+        let mut chain = create_chain(3);
+        chain[0].parent_hash = [1; 32];
+        assert_eq!(
+            consensus.validate_chain(&chain),
+            Err(ChainError::InvalidGenesis.into())
+        );
+        assert_eq!(
+            consensus.validate_chain(&create_chain(3)[1..]),
+            Err(ChainError::InvalidGenesis.into())
+        );
+    }
+
+    #[test]
+    fn test_validate_chain() {
+        let consensus = DensityConsensus::new();
+        let chain = create_chain(6);
+        assert_eq!(consensus.validate_chain(&chain), Ok(()));
+        assert_eq!(consensus.validate_chain(&[]), Ok(()));
+
+        let mut broken = chain.clone();
+        broken[3].parent_hash = [7; 32];
+        assert_eq!(
+            consensus.validate_chain(&broken),
+            Err(ChainError::BrokenLink { index: 3 }.into())
+        );
+
+        let mut tampered = chain.clone();
+        tampered[4].state_proof = chain[2].state_proof.clone();
+        assert_eq!(
+            consensus.validate_chain(&tampered),
+            Err(ChainError::InvalidProof { index: 4 }.into())
+        );
+
+        let mut skipped = chain[..3].to_vec();
+        skipped.push(Block {
+            height: 4,
+            ..create_child(&chain[2], 3 * SLOT_DURATION)
+        });
+        assert_eq!(
+            consensus.validate_chain(&skipped),
+            Err(ChainError::HeightGap { index: 3 }.into())
+        );
+
+        let mut regressed = chain[..3].to_vec();
+        regressed.push(create_child(&chain[2], SLOT_DURATION));
+        assert_eq!(
+            consensus.validate_chain(&regressed),
+            Err(ChainError::TimestampRegression { index: 3 }.into())
+        );
+
+        let mut same_slot = chain[..3].to_vec();
+        same_slot.push(create_child(&chain[2], chain[2].timestamp));
+        assert_eq!(
+            consensus.validate_chain(&same_slot),
+            Err(ChainError::TimestampRegression { index: 3 }.into())
+        );
+    }
+
+    #[test]
+    fn test_long_range_fork_decided_after_fork_point() {
+        let prefix: Vec<Block> = (0..5)
+            .map(|i| create_block(i, (i + 1) * SLOT_DURATION))
+            .collect();
+
+        // Honest fork fills every slot right after the fork point
+        let mut honest = prefix.clone();
+        honest.extend((6..=45).map(|slot| create_block(slot, slot * SLOT_DURATION)));
+
+        // Attacker fork is nearly empty after the fork point but packs many
+        // more blocks in later, ending up longer
+        let mut attacker = prefix.clone();
+        let attacker_slots = [6, 30].into_iter().chain(50..=94);
+        attacker.extend(attacker_slots.map(|slot| create_block(slot, slot * SLOT_DURATION)));
+        assert!(attacker.len() > honest.len());
+
+        // Plain length comparison is fooled
+        let plain = DensityConsensus::new();
+        assert_eq!(plain.choose_fork(&honest, &attacker).len(), attacker.len());
+
+        let genesis = DensityConsensus::with_security_param(10);
+        assert_eq!(genesis.choose_fork(&honest, &attacker).len(), honest.len());
+        assert_eq!(genesis.choose_fork(&attacker, &honest).len(), honest.len());
+
+        // Short forks still fall back to the regular rule
+        let short = &attacker[..prefix.len() + 3];
+        let other = &honest[..prefix.len() + 5];
+        assert_eq!(genesis.choose_fork(short, other).len(), other.len());
+    }
+
+    #[test]
+    fn test_fork_point() {
+        let shared = create_chain(5);
+        let mut chain_a = shared.clone();
+        let mut chain_b = shared;
+        for i in 5..8u64 {
+            let child = create_child(chain_a.last().unwrap(), i * SLOT_DURATION);
+            chain_a.push(child);
+        }
+        for i in 5..7u64 {
+            let child = create_child(chain_b.last().unwrap(), (i + 10) * SLOT_DURATION);
+            chain_b.push(child);
+        }
+
+        assert_eq!(DensityConsensus::fork_point(&chain_a, &chain_b), Some(4));
+        assert_eq!(DensityConsensus::fork_point(&chain_b, &chain_a), Some(4));
+        assert_eq!(DensityConsensus::fork_point(&chain_a, &chain_a), Some(7));
+        // A chain is a prefix of its own extension
+        assert_eq!(
+            DensityConsensus::fork_point(&chain_a[..3], &chain_a),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_fork_point_without_shared_genesis() {
+        let chain_a = create_chain(4);
+        let other_genesis = create_block(0, 7 * SLOT_DURATION);
+        let chain_b = vec![other_genesis.clone(), create_child(&other_genesis, 8)];
+
+        assert_eq!(DensityConsensus::fork_point(&chain_a, &chain_b), None);
+        assert_eq!(DensityConsensus::fork_point(&chain_a, &[]), None);
+    }
+
+    #[test]
+    fn test_fork_ties_are_broken_canonically() {
+        let consensus = DensityConsensus::new();
+
+        // Recent forks of equal length
+        let a: Vec<Block> = (0..4).map(|i| create_block(i, i * SLOT_DURATION)).collect();
+        let b: Vec<Block> = (0..4)
+            .map(|i| create_block(i + 10, i * SLOT_DURATION))
+            .collect();
+        let ab = consensus.choose_fork(&a, &b).last().unwrap().hash();
+        let ba = consensus.choose_fork(&b, &a).last().unwrap().hash();
+        assert_eq!(ab, ba);
+        assert_eq!(ab, a.last().unwrap().hash().min(b.last().unwrap().hash()));
+
+        // Older forks of equal density
+        let c: Vec<Block> = (0..4)
+            .map(|i| create_block(i + 20, (100 + i) * SLOT_DURATION))
+            .collect();
+        assert_eq!(
+            consensus.calculate_density(&a),
+            consensus.calculate_density(&c)
+        );
+        let ac = consensus.choose_fork(&a, &c).last().unwrap().hash();
+        let ca = consensus.choose_fork(&c, &a).last().unwrap().hash();
+        assert_eq!(ac, ca);
+    }
+
+    #[test]
+    fn test_default_matches_new() {
+        let short = create_chain(4);
+        let long = create_chain(7);
+        let sparse: Vec<Block> = (0..5)
+            .map(|i| create_block(i, i * 3 * SLOT_DURATION))
+            .collect();
+
+        let (default, new) = (DensityConsensus::default(), DensityConsensus::new());
+        for (a, b) in [(&short, &long), (&long, &sparse), (&sparse, &short)] {
+            let chosen = default.choose_fork(a, b).last().unwrap().hash();
+            assert_eq!(chosen, new.choose_fork(a, b).last().unwrap().hash());
+        }
+        assert_eq!(
+            default.calculate_density(&sparse),
+            new.calculate_density(&sparse)
+        );
+    }
+
+    #[test]
+    fn test_choose_fork_explained() {
+        let consensus = DensityConsensus::new();
+
+        // Recent fork: decided by length
+        let short = create_chain(4);
+        let long = create_chain(6);
+        let (chain, decision) = consensus.choose_fork_explained(&short, &long);
+        assert_eq!(chain.len(), 6);
+        assert_eq!(decision, ForkDecision::ByLength { len_a: 4, len_b: 6 });
+
+        // Old fork: decided by density, even against a longer chain
+        let sparse: Vec<Block> = (0..8)
+            .map(|i| create_block(i, (100 + 3 * i) * SLOT_DURATION))
+            .collect();
+        let (chain, decision) = consensus.choose_fork_explained(&sparse, &short);
+        assert_eq!(chain.len(), short.len());
+        assert_eq!(
+            decision,
+            ForkDecision::ByDensity {
+                density_a: consensus.calculate_density(&sparse),
+                density_b: consensus.calculate_density(&short),
+            }
+        );
+
+        // Long-range fork past the security parameter
+        let secure = DensityConsensus::with_security_param(1);
+        let (_, decision) = secure.choose_fork_explained(&short, &long);
+        assert!(matches!(decision, ForkDecision::LongRange { .. }));
+
+        for (a, b) in [(&short, &long), (&sparse, &short), (&long, &sparse)] {
+            let (chain, _) = consensus.choose_fork_explained(a, b);
+            assert_eq!(chain.len(), consensus.choose_fork(a, b).len());
+        }
+        assert_eq!(
+            consensus.choose_fork_explained(&[], &short).1,
+            ForkDecision::EmptyChain
+        );
+    }
+
+    #[test]
+    fn test_choose_fork_with_empty_chains() {
+        let chain: Vec<Block> = (0..3).map(|i| create_block(i, i * SLOT_DURATION)).collect();
+        let empty: Vec<Block> = Vec::new();
+
+        for consensus in [
+            DensityConsensus::new(),
+            DensityConsensus::with_security_param(1),
+        ] {
+            assert_eq!(consensus.choose_fork(&empty, &chain).len(), 3);
+            assert_eq!(consensus.choose_fork(&chain, &empty).len(), 3);
+            assert!(consensus.choose_fork(&empty, &empty).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_window_densities_show_gaps() {
+        let consensus = DensityConsensus::with_config(2, SLOT_DURATION);
+        let slots = [0, 1, 2, 3, 10, 11, 12, 13, 14];
+        let chain: Vec<Block> = slots
+            .iter()
+            .enumerate()
+            .map(|(i, &slot)| create_block(i as u64, slot * SLOT_DURATION))
+            .collect();
+
+        let densities = consensus.window_densities(&chain);
+        assert_eq!(densities.len(), chain.len());
+        for (i, &density) in densities.iter().enumerate() {
+            // Windows starting at blocks 2 and 3 reach across the gap
+            if i == 2 || i == 3 {
+                assert!(density < 1.0, "window {} has density {}", i, density);
+            } else {
+                assert!(density >= 1.0, "window {} has density {}", i, density);
+            }
+        }
+
+        let average = densities.iter().sum::<f64>() / densities.len() as f64;
+        assert_eq!(consensus.calculate_density(&chain), average);
+        assert!(consensus.window_densities(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_tracker_matches_batch_density() {
+        let consensus = DensityConsensus::with_config(3, SLOT_DURATION);
+        let slots = [0, 1, 1, 4, 5, 9, 10, 11, 20, 21];
+        let chain: Vec<Block> = slots
+            .iter()
+            .enumerate()
+            .map(|(i, &slot)| create_block(i as u64, slot * SLOT_DURATION))
+            .collect();
+
+        let mut tracker = DensityTracker::new(&consensus);
+        assert_eq!(tracker.current_density(), 0.0);
+        for (n, block) in chain.iter().enumerate() {
+            tracker.push_block(block);
+            let start = (n + 1).saturating_sub(tracker.len());
+            assert_eq!(tracker.len(), (n + 1).min(4));
+            assert_eq!(
+                tracker.current_density(),
+                consensus.calculate_density(&chain[start..=n]),
+                "after block {}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn test_weighted_density_prefers_more_work() {
+        let consensus = DensityConsensus::new();
+        let block_with_state = |height: u64, size: u64| {
+            let state = (0..size).map(FieldElement::new).collect();
+            Block::new([0; 32], height, height * SLOT_DURATION, state)
+        };
+
+        let light: Vec<Block> = (0..6).map(|i| block_with_state(i, 1)).collect();
+        let heavy: Vec<Block> = (0..6).map(|i| block_with_state(i, 8)).collect();
 
-        // Calculate average density across sliding windows
-        let mut total_density = 0.0;
+        assert_eq!(
+            consensus.calculate_density(&light),
+            consensus.calculate_density(&heavy)
+        );
+        assert!(
+            consensus.calculate_weighted_density(&heavy)
+                > consensus.calculate_weighted_density(&light)
+        );
+        assert_eq!(
+            consensus.calculate_weighted_density(&heavy),
+            8.0 * consensus.calculate_weighted_density(&light)
+        );
+    }
+
+    #[test]
+    fn test_inflated_state_len_breaks_the_chain() {
+        let consensus = DensityConsensus::new();
+        let chain = create_chain(6);
+        assert_eq!(consensus.validate_chain(&chain), Ok(()));
+
+        // A relaying peer claiming more state to raise the weighted density
+        let mut inflated = chain.clone();
+        inflated[2].state_len = 64;
+        assert!(
+            consensus.calculate_weighted_density(&inflated)
+                > consensus.calculate_weighted_density(&chain)
+        );
+        assert_ne!(inflated[2].hash(), chain[2].hash());
+        assert_eq!(
+            consensus.validate_chain(&inflated),
+            Err(ChainError::BrokenLink { index: 3 }.into())
+        );
+    }
 
-        for i in 0..num_windows {
-            let start_block = &blocks[i as usize];
-            let end_idx = ((i + window_size).min(blocks.len() as u64 - 1)) as usize;
-            let end_block = &blocks[end_idx];
+    #[test]
+    fn test_producer_cannot_claim_state_past_the_proof() {
+        let consensus = DensityConsensus::new();
+        let genesis = create_block(0, 0);
 
-            let window_density = self.window_density(
-                &blocks[i as usize..=end_idx],
-                start_block.timestamp / self.slot_duration,
-                end_block.timestamp / self.slot_duration,
+        // The producer hashes its own inflated length, so the link holds and
+        // only the proof's degree bound can catch it
+        for claimed in [0, 65, usize::MAX] {
+            let mut lying = create_child(&genesis, SLOT_DURATION);
+            lying.state_len = claimed;
+            assert_eq!(
+                lying.check_state_proof(),
+                Err(VerifyError::DegreeOutOfRange.into())
             );
+            assert_eq!(
+                consensus.validate_chain(&[genesis.clone(), lying]),
+                Err(ChainError::InvalidProof { index: 1 }.into())
+            );
+        }
+
+        // The bound itself is the most a block can weigh
+        let mut capped = create_child(&genesis, SLOT_DURATION);
+        capped.state_len = capped.state_proof.degree_bound();
+        assert_eq!(capped.check_state_proof(), Ok(()));
+    }
+
+    #[test]
+    fn test_chain_growth_on_regular_chain() {
+        let consensus = DensityConsensus::new();
+
+        // One block every other slot
+        let mut chain = vec![create_block(0, 0)];
+        for i in 1..20u64 {
+            let child = create_child(&chain[i as usize - 1], 2 * i * SLOT_DURATION);
+            chain.push(child);
+        }
+
+        assert_eq!(consensus.chain_growth(&chain, 10), 0.5);
+        // Window reaching past genesis is clamped to the first block
+        assert_eq!(consensus.chain_growth(&chain, 1_000), 0.5);
+        assert_eq!(consensus.chain_growth(&create_chain(8), 4), 1.0);
+
+        assert_eq!(consensus.chain_growth(&chain, 0), 0.0);
+        assert_eq!(consensus.chain_growth(&chain[..1], 10), 0.0);
+        assert_eq!(consensus.chain_growth(&[], 10), 0.0);
+    }
+
+    #[test]
+    fn test_slot_leader_eligibility() {
+        let consensus = DensityConsensus::new();
+        let total = 1_000;
+        let mut wins = [0usize; 3];
+
+        for slot in 0..200u64 {
+            let vrf_output = Sha256::digest(slot.to_be_bytes());
+            assert!(consensus.is_slot_leader(slot, total, total, &vrf_output));
+            assert!(!consensus.is_slot_leader(slot, 0, total, &vrf_output));
 
-            total_density += window_density;
+            // More stake never loses eligibility
+            let small = consensus.is_slot_leader(slot, 100, total, &vrf_output);
+            let large = consensus.is_slot_leader(slot, 500, total, &vrf_output);
+            assert!(!small || large);
+            wins[0] += small as usize;
+            wins[1] += large as usize;
+            wins[2] += consensus.is_slot_leader(slot, 900, total, &vrf_output) as usize;
+        }
+        assert!(wins[0] > 0 && wins[0] < wins[1] && wins[1] < wins[2] && wins[2] < 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake cannot exceed total stake")]
+    fn test_slot_leader_rejects_excess_stake() {
+        DensityConsensus::new().is_slot_leader(0, 2, 1, &[0; 32]);
+    }
+
+    #[test]
+    fn test_density_threshold() {
+        let consensus = DensityConsensus::new();
+        let dense = create_chain(20);
+        assert!(consensus.meets_density_threshold(&dense, 0.9, 10));
+
+        // Regular early on, then a block only every fourth slot
+        let mut gappy = create_chain(10);
+        for i in 1..=5u64 {
+            let child = create_child(gappy.last().unwrap(), (9 + 4 * i) * SLOT_DURATION);
+            gappy.push(child);
+        }
+        assert!(!consensus.meets_density_threshold(&gappy, 0.9, 10));
+        assert!(consensus.meets_density_threshold(&gappy, 0.25, 10));
+        // The whole chain averages out higher than its tail
+        assert!(consensus.meets_density_threshold(&gappy, 0.45, 1_000));
+        assert!(!consensus.meets_density_threshold(&[], 0.1, 10));
+    }
+
+    #[test]
+    fn test_density_of_degenerate_windows_is_finite() {
+        let consensus = DensityConsensus::new();
+
+        let single = vec![create_block(0, 7 * SLOT_DURATION)];
+        let density = consensus.calculate_density(&single);
+        assert!(density.is_finite());
+        assert_eq!(density, 1.0);
+
+        assert_eq!(consensus.window_density(&single, 7, 7), 1.0);
+        assert_eq!(consensus.calculate_density(&[]), 0.0);
+
+        let chain: Vec<Block> = (0..5).map(|i| create_block(i, i * SLOT_DURATION)).collect();
+        assert!(consensus.calculate_density(&chain).is_finite());
+    }
+
+    #[test]
+    fn test_unsorted_timestamps_give_finite_density() {
+        let consensus = DensityConsensus::with_config(2, SLOT_DURATION);
+        assert_eq!(consensus.expected_slots(10, 5), 0);
+
+        let slots = [5, 1, 2, 3, 4, 10];
+        let blocks: Vec<Block> = slots
+            .iter()
+            .enumerate()
+            .map(|(i, &slot)| create_block(i as u64, slot * SLOT_DURATION))
+            .collect();
+
+        // The window starting at slot 5 and ending at slot 2 is skipped
+        let densities = consensus.window_densities(&blocks);
+        assert_eq!(densities.len(), slots.len() - 1);
+        let density = consensus.calculate_density(&blocks);
+        assert!(density.is_finite());
+        assert!(density > 0.0 && density <= 1.5);
+
+        // Only the one-block window at the end of a backwards pair counts
+        let backwards = blocks[..2].to_vec();
+        assert_eq!(consensus.window_densities(&backwards), [1.0]);
+        assert_eq!(consensus.calculate_density(&backwards), 1.0);
+    }
+
+    #[test]
+    fn test_equivocation_does_not_inflate_density() {
+        let consensus = DensityConsensus::new();
+
+        let canonical: Vec<Block> = (0..10)
+            .map(|i| create_block(i, i * 2 * SLOT_DURATION))
+            .collect();
+
+        // Flood two slots with extra blocks that reuse an existing timestamp
+        let mut padded = Vec::new();
+        for block in &canonical {
+            padded.push(block.clone());
+            if block.height == 3 || block.height == 7 {
+                for _ in 0..3 {
+                    padded.push(create_block(block.height, block.timestamp));
+                }
+            }
         }
 
-        total_density / num_windows as f64
+        let last_slot = canonical.last().unwrap().timestamp / SLOT_DURATION;
+        assert!(
+            consensus.window_density(&padded, 0, last_slot)
+                > consensus.window_density(&canonical, 0, last_slot)
+        );
+
+        assert_eq!(
+            consensus.density_ignoring_equivocation(&padded),
+            consensus.calculate_density(&canonical)
+        );
+        assert_eq!(
+            consensus.density_ignoring_equivocation(&canonical),
+            consensus.calculate_density(&canonical)
+        );
     }
 }
@@ -1,9 +1,8 @@
 // src/consensus/mod.rs
 
+pub mod clock;
 pub mod density;
 
-use crate::crypto::field::FieldElement;
-
 pub trait Consensus {
     type Block;
     type State;
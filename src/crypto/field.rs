@@ -1,19 +1,136 @@
 // src/crypto/field.rs
 
+use alloc::vec::Vec;
+use core::iter::{Product, Sum};
+use core::ops::{Add, Div, Mul, Sub};
+#[cfg(feature = "std")]
 use rand::random;
-use std::ops::{Add, Div, Mul, Sub};
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 pub const FIELD_PRIME: u64 = 2_147_483_647;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct FieldElement {
+// Largest power of two dividing FIELD_PRIME - 1, and a root of unity of that
+// order. p - 1 = 2 · 3² · 7 · 11 · 31 · 151 · 331, so the two-adicity is only
+// 1 and the root is -1: power-of-two NTTs beyond size 2 don't exist here.
+pub const TWO_ADICITY: u32 = (FIELD_PRIME - 1).trailing_zeros();
+pub const TWO_ADIC_ROOT: u64 = two_adic_root(FIELD_PRIME);
+
+const fn mul_mod(a: u64, b: u64, p: u64) -> u64 {
+    (a as u128 * b as u128 % p as u128) as u64
+}
+
+const fn pow_mod(mut base: u64, mut exp: u64, p: u64) -> u64 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, p);
+        }
+        base = mul_mod(base, base, p);
+        exp >>= 1;
+    }
+    result
+}
+
+// z^((p - 1) / 2^s) for the smallest quadratic non-residue z, which has
+// order exactly 2^s where s is the two-adicity of p
+const fn two_adic_root(p: u64) -> u64 {
+    let mut z = 2;
+    while pow_mod(z, (p - 1) / 2, p) != p - 1 {
+        z += 1;
+    }
+    pow_mod(z, (p - 1) >> (p - 1).trailing_zeros(), p)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum FieldError {
+    #[error("packed buffer has {actual} bytes, expected {expected}")]
+    LengthMismatch { expected: usize, actual: usize },
+    #[error("value {0} is not reduced modulo the field prime")]
+    NonCanonical(u64),
+    #[error("division by zero")]
+    DivByZero,
+}
+
+// `count` uniform field elements derived from `data`: SHA-256 of `data`
+// followed by a little-endian u64 counter gives blocks of four 8-byte
+// candidates, each masked to `BITS` bits and kept only if below the
+// modulus, so there is no modulo bias. Fiat-Shamir challenges are drawn
+// through this.
+pub fn hash_to_field(data: &[u8], count: usize) -> Vec<FieldElement> {
+    hash_to_fp(data, count)
+}
+
+pub(crate) fn hash_to_fp<const P: u64>(data: &[u8], count: usize) -> Vec<Fp<P>> {
+    let mask = u64::MAX >> (u64::BITS - Fp::<P>::BITS);
+    let mut elements = Vec::with_capacity(count);
+    let mut counter = 0u64;
+    while elements.len() < count {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.update(counter.to_le_bytes());
+        let block = hasher.finalize();
+        for chunk in block.chunks_exact(8) {
+            let candidate = u64::from_le_bytes(chunk.try_into().unwrap()) & mask;
+            if candidate < P && elements.len() < count {
+                elements.push(Fp { value: candidate });
+            }
+        }
+        counter += 1;
+    }
+    elements
+}
+
+// Map uniform draws from `0..range` onto `0..modulus` without modulo bias by
+// rejecting draws that fall in the incomplete block at the top of the range
+#[cfg(feature = "std")]
+fn sample_below(modulus: u64, range: u128, mut draw: impl FnMut() -> u64) -> u64 {
+    let modulus = modulus as u128;
+    let limit = range - range % modulus;
+    loop {
+        let candidate = draw() as u128;
+        if candidate < limit {
+            return (candidate % modulus) as u64;
+        }
+    }
+}
+
+// Element of the prime field with modulus `P`. `P` must be prime; every
+// operation (including `inverse` and `pow`) reduces modulo it. Values are
+// kept canonical, so the derived ordering is numeric order on 0..P.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Fp<const P: u64> {
     value: u64,
 }
 
-impl FieldElement {
+// The Mersenne prime 2^31 - 1 used throughout the crate
+pub type Mersenne31 = Fp<FIELD_PRIME>;
+pub type FieldElement = Mersenne31;
+
+// 2^64 - 2^32 + 1, whose two-adicity of 32 allows power-of-two NTTs up to
+// 2^32 points. Not used by the accumulator, which stays on Mersenne31.
+pub const GOLDILOCKS_PRIME: u64 = 0xffff_ffff_0000_0001;
+pub type Goldilocks = Fp<GOLDILOCKS_PRIME>;
+pub type NttField = Goldilocks;
+
+impl<const P: u64> Fp<P> {
+    pub const MODULUS: u64 = P;
+
+    // Every canonical element fits in this many bits, which is what `pack_many` uses
+    pub const BITS: u32 = u64::BITS - (P - 1).leading_zeros();
+
+    pub const TWO_ADICITY: u32 = (P - 1).trailing_zeros();
+    pub const TWO_ADIC_ROOT: u64 = two_adic_root(P);
+
     pub fn new(value: u64) -> Self {
-        FieldElement {
-            value: value % FIELD_PRIME,
+        Fp { value: value % P }
+    }
+
+    // Reduces a wide intermediate, such as a product of two canonical
+    // values, the same way `Mul` does
+    pub fn from_u128(value: u128) -> Self {
+        Fp {
+            value: (value % P as u128) as u64,
         }
     }
 
@@ -22,98 +139,628 @@ impl FieldElement {
     }
 
     pub fn zero() -> Self {
-        FieldElement { value: 0 }
+        Fp { value: 0 }
     }
 
     pub fn one() -> Self {
-        FieldElement { value: 1 }
+        Fp { value: 1 }
     }
 
-    pub fn random() -> Self {
-        FieldElement::new(random::<u64>())
+    // Every constructor reduces, so an unreduced value means a bug elsewhere
+    fn debug_assert_canonical(&self) {
+        debug_assert!(self.value < P, "Non-canonical field element {}", self.value);
+    }
+
+    // Same result as `==`, but without data-dependent branches. Values are
+    // always canonical, so comparing them compares the elements.
+    pub fn ct_eq(&self, other: &Self) -> Choice {
+        self.value.ct_eq(&other.value)
     }
 
-    pub fn pow(&self, exp: usize) -> Self {
-        if exp == 0 {
-            return Self::one();
+    // `a` if `choice` is set, `b` otherwise, without branching on `choice`.
+    // Note the order is the reverse of `subtle::ConditionallySelectable`.
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Fp {
+            value: u64::conditional_select(&b.value, &a.value, choice),
         }
-        let mut result = *self;
-        let mut exp = exp;
+    }
+
+    // Needs an OS-backed RNG, so it is only available with `std`
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Fp {
+            value: sample_below(P, 1 << 64, random::<u64>),
+        }
+    }
+
+    // Square-and-multiply; takes u64 so exponents up to the modulus fit on every target
+    pub fn pow(&self, exp: u64) -> Self {
+        let mut result = Self::one();
         let mut base = *self;
+        let mut exp = exp;
 
-        while exp > 1 {
-            if exp % 2 == 1 {
+        while exp > 0 {
+            if exp & 1 == 1 {
                 result = result * base;
             }
             base = base * base;
-            exp /= 2;
-        }
-        if exp == 1 {
-            result = result * base;
+            exp >>= 1;
         }
         result
     }
 
+    // Raises to the exponent's canonical value. Exponents act modulo the
+    // group order p - 1, not p: an exponent that went through field
+    // arithmetic has been reduced mod p, so it is generally not the integer
+    // the caller computed. The value is used as is rather than reduced mod
+    // p - 1, which would turn 0^(p-1) into 0^0.
+    pub fn pow_fe(&self, exp: Self) -> Self {
+        self.pow(exp.value)
+    }
+
+    // Element of multiplicative order exactly `n`, for `n` a power of two up
+    // to 2^TWO_ADICITY; `None` for any other `n`
+    pub fn root_of_unity(n: u64) -> Option<Self> {
+        if !n.is_power_of_two() || n.trailing_zeros() > Self::TWO_ADICITY {
+            return None;
+        }
+        let root = Fp {
+            value: Self::TWO_ADIC_ROOT,
+        };
+        Some(root.pow(1 << (Self::TWO_ADICITY - n.trailing_zeros())))
+    }
+
     pub fn inverse(&self) -> Option<Self> {
         if self.value == 0 {
             return None;
         }
         // Using Fermat's little theorem: a^(p-1) ≡ 1 (mod p)
         // Therefore, a^(p-2) is the multiplicative inverse
-        Some(self.pow((FIELD_PRIME - 2) as usize))
+        Some(self.pow(P - 2))
+    }
+
+    // `self / other`, failing instead of panicking when `other` is zero
+    pub fn try_div(self, other: Self) -> Result<Self, FieldError> {
+        other
+            .inverse()
+            .map(|inv| self * inv)
+            .ok_or(FieldError::DivByZero)
+    }
+
+    // Number of bytes `pack_many` produces for `count` elements
+    pub fn packed_len(count: usize) -> usize {
+        (count * Self::BITS as usize).div_ceil(8)
+    }
+
+    // Serialize elements back to back using `BITS` bits each (31 for
+    // Mersenne31) in little-endian bit order
+    pub fn pack_many(elements: &[Self]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::packed_len(elements.len()));
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+
+        for element in elements {
+            acc |= (element.value as u128) << bits;
+            bits += Self::BITS;
+            while bits >= 8 {
+                out.push(acc as u8);
+                acc >>= 8;
+                bits -= 8;
+            }
+        }
+        if bits > 0 {
+            out.push(acc as u8);
+        }
+
+        out
+    }
+
+    // Inverse of `pack_many`; rejects buffers of the wrong size and unreduced values
+    pub fn unpack_many(bytes: &[u8], count: usize) -> Result<Vec<Self>, FieldError> {
+        let expected = Self::packed_len(count);
+        if bytes.len() != expected {
+            return Err(FieldError::LengthMismatch {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let mask = (1u128 << Self::BITS) - 1;
+        let mut out = Vec::with_capacity(count);
+        let mut bytes = bytes.iter();
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+
+        for _ in 0..count {
+            while bits < Self::BITS {
+                // The length check above guarantees enough input bytes
+                acc |= (*bytes.next().unwrap() as u128) << bits;
+                bits += 8;
+            }
+            let value = (acc & mask) as u64;
+            acc >>= Self::BITS;
+            bits -= Self::BITS;
+
+            if value >= P {
+                return Err(FieldError::NonCanonical(value));
+            }
+            out.push(Fp { value });
+        }
+
+        Ok(out)
     }
 }
 
 // Add From<u64> implementation
-impl From<u64> for FieldElement {
+impl<const P: u64> From<u64> for Fp<P> {
     fn from(value: u64) -> Self {
-        FieldElement::new(value)
+        Fp::new(value)
     }
 }
 
 // Implement remaining operator traits
-impl Add for FieldElement {
+impl<const P: u64> Add for Fp<P> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        let sum = self.value + other.value;
-        FieldElement::new(if sum >= FIELD_PRIME {
-            sum - FIELD_PRIME
+        self.debug_assert_canonical();
+        other.debug_assert_canonical();
+        // Both inputs are below P, so one conditional subtract reduces the
+        // sum; in u128 it can't wrap, even for moduli close to 2^64
+        let sum = self.value as u128 + other.value as u128;
+        let value = if sum >= P as u128 {
+            sum - P as u128
         } else {
             sum
-        })
+        };
+        Fp {
+            value: value as u64,
+        }
     }
 }
 
-impl Sub for FieldElement {
+impl<const P: u64> Sub for Fp<P> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        let diff = if self.value >= other.value {
+        self.debug_assert_canonical();
+        other.debug_assert_canonical();
+        let value = if self.value >= other.value {
             self.value - other.value
         } else {
-            FIELD_PRIME - (other.value - self.value)
+            // P - other.value is positive, and adding self.value stays below P
+            P - other.value + self.value
         };
-        FieldElement::new(diff)
+        Fp { value }
     }
 }
 
-impl Mul for FieldElement {
+impl<const P: u64> Mul for Fp<P> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
-        FieldElement::new((self.value as u128 * other.value as u128 % FIELD_PRIME as u128) as u64)
+        Fp::from_u128(self.value as u128 * other.value as u128)
     }
 }
 
-impl Div for FieldElement {
+impl<const P: u64> Div for Fp<P> {
     type Output = Self;
 
+    // Panics on a zero divisor; `try_div` is the fallible form
     fn div(self, other: Self) -> Self {
-        if let Some(inv) = other.inverse() {
-            self * inv
-        } else {
-            panic!("Division by zero")
+        self.try_div(other).expect("Division by zero")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const P: u64> zeroize::Zeroize for Fp<P> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+// Elements travel as their canonical u64; decoding reduces modulo P so
+// untrusted input can never produce an unreduced element
+#[cfg(feature = "serde")]
+impl<const P: u64> serde::Serialize for Fp<P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const P: u64> serde::Deserialize<'de> for Fp<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(Fp::new)
+    }
+}
+
+impl<const P: u64> Sum for Fp<P> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<'a, const P: u64> Sum<&'a Fp<P>> for Fp<P> {
+    fn sum<I: Iterator<Item = &'a Fp<P>>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<const P: u64> Product for Fp<P> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::one(), |acc, x| acc * x)
+    }
+}
+
+impl<'a, const P: u64> Product<&'a Fp<P>> for Fp<P> {
+    fn product<I: Iterator<Item = &'a Fp<P>>>(iter: I) -> Self {
+        iter.copied().product()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_ordering_by_canonical_value() {
+        let mut elements: Vec<FieldElement> = [
+            7,
+            3,
+            FIELD_PRIME + 3,
+            0,
+            FIELD_PRIME - 1,
+            2 * FIELD_PRIME + 7,
+        ]
+        .into_iter()
+        .map(FieldElement::new)
+        .collect();
+        elements.sort();
+
+        let values: Vec<u64> = elements.iter().map(|e| e.value()).collect();
+        assert_eq!(values, [0, 3, 3, 7, 7, FIELD_PRIME - 1]);
+        assert_eq!(
+            FieldElement::new(FIELD_PRIME + 3).cmp(&FieldElement::new(3)),
+            core::cmp::Ordering::Equal
+        );
+        assert!(FieldElement::new(FIELD_PRIME - 1) > FieldElement::new(FIELD_PRIME));
+    }
+
+    #[test]
+    fn test_ct_eq_matches_eq() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..1000 {
+            let a = FieldElement::new(rng.gen());
+            let b = if rng.gen() {
+                FieldElement::new(a.value() + FIELD_PRIME)
+            } else {
+                FieldElement::new(rng.gen())
+            };
+            assert_eq!(bool::from(a.ct_eq(&b)), a == b);
+            assert!(bool::from(a.ct_eq(&a)));
+        }
+        assert!(!bool::from(
+            FieldElement::zero().ct_eq(&FieldElement::one())
+        ));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use zeroize::Zeroize;
+        let mut a = FieldElement::new(123_456);
+        a.zeroize();
+        assert_eq!(a, FieldElement::zero());
+    }
+
+    #[test]
+    fn test_conditional_select() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..1000 {
+            let a = FieldElement::new(rng.gen());
+            let b = FieldElement::new(rng.gen());
+            assert_eq!(FieldElement::conditional_select(&a, &b, Choice::from(1)), a);
+            assert_eq!(FieldElement::conditional_select(&a, &b, Choice::from(0)), b);
+        }
+    }
+
+    fn chi_squared(counts: &[u64], samples: u64) -> f64 {
+        let expected = samples as f64 / counts.len() as f64;
+        counts
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    #[test]
+    fn test_random_sampling_is_unbiased() {
+        // Byte-sized draws over a prime just below 256 make the bias of a
+        // plain `% p` reduction easy to measure
+        const TEST_PRIME: u64 = 251;
+        const SAMPLES: u64 = TEST_PRIME * 200;
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut naive = vec![0u64; TEST_PRIME as usize];
+        let mut rejected = vec![0u64; TEST_PRIME as usize];
+        for _ in 0..SAMPLES {
+            naive[(rng.gen::<u8>() as u64 % TEST_PRIME) as usize] += 1;
+            let value = sample_below(TEST_PRIME, 256, || rng.gen::<u8>() as u64);
+            rejected[value as usize] += 1;
+        }
+
+        // 250 degrees of freedom: mean 250, standard deviation ~22
+        let threshold = 400.0;
+        assert!(chi_squared(&naive, SAMPLES) > threshold);
+        assert!(chi_squared(&rejected, SAMPLES) < threshold);
+    }
+
+    #[test]
+    fn test_pow_matches_repeated_multiplication() {
+        let bases = [
+            FieldElement::zero(),
+            FieldElement::one(),
+            FieldElement::new(2),
+            FieldElement::new(123_456_789),
+            FieldElement::new(FIELD_PRIME - 1),
+        ];
+
+        for base in bases {
+            let mut naive = FieldElement::one();
+            for exp in 0..=70u64 {
+                assert_eq!(base.pow(exp), naive, "{:?}^{}", base, exp);
+                naive = naive * base;
+            }
+        }
+    }
+
+    #[test]
+    fn test_pow_full_field_order() {
+        let a = FieldElement::new(987_654_321);
+        // Fermat: a^(p-1) = 1 and a^p = a
+        assert_eq!(a.pow(FIELD_PRIME - 1), FieldElement::one());
+        assert_eq!(a.pow(FIELD_PRIME), a);
+        assert_eq!(a * a.inverse().unwrap(), FieldElement::one());
+    }
+
+    #[test]
+    fn test_two_adic_root_of_unity() {
+        assert_eq!(TWO_ADICITY, 1);
+        assert_eq!(TWO_ADIC_ROOT, FIELD_PRIME - 1);
+
+        let root = FieldElement::root_of_unity(2).unwrap();
+        assert_eq!(root * root, FieldElement::one());
+        assert_ne!(root, FieldElement::one());
+        assert_eq!(FieldElement::root_of_unity(1), Some(FieldElement::one()));
+        assert_eq!(FieldElement::root_of_unity(4), None);
+        assert_eq!(FieldElement::root_of_unity(6), None);
+    }
+
+    #[test]
+    fn test_ntt_field_roots_of_unity() {
+        assert_eq!(NttField::TWO_ADICITY, 32);
+
+        let root = NttField::root_of_unity(1024).unwrap();
+        assert_eq!(root.pow(1024), NttField::one());
+        assert_ne!(root.pow(512), NttField::one());
+
+        let a = NttField::new(GOLDILOCKS_PRIME - 1);
+        assert_eq!(a + NttField::new(2), NttField::one());
+        assert_eq!(a * a.inverse().unwrap(), NttField::one());
+    }
+
+    #[test]
+    fn test_pow_fe() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let order = FieldElement::new(FIELD_PRIME - 1);
+        for _ in 0..100 {
+            let a = FieldElement::new(rng.gen_range(1..FIELD_PRIME));
+            let e = FieldElement::new(rng.gen());
+            assert_eq!(a.pow_fe(order), FieldElement::one());
+            assert_eq!(a.pow_fe(e), a.pow(e.value()));
+            // a^(e + p - 1) = a^e
+            assert_eq!(a.pow(e.value() + FIELD_PRIME - 1), a.pow_fe(e));
         }
+        assert_eq!(FieldElement::zero().pow_fe(order), FieldElement::zero());
+        assert_eq!(
+            FieldElement::zero().pow_fe(FieldElement::zero()),
+            FieldElement::one()
+        );
+    }
+
+    #[test]
+    fn test_from_u128() {
+        // (2^40)^2 = 2^80, above 2^64
+        let a = FieldElement::new(1 << 40);
+        let wide = (1u128 << 40) * (1u128 << 40);
+        assert_eq!(FieldElement::from_u128(wide), a * a);
+        assert_eq!(
+            FieldElement::from_u128(wide).value() as u128,
+            wide % FIELD_PRIME as u128
+        );
+        // 2^120 still fits: three factors reduced once match two `Mul`s
+        assert_eq!(FieldElement::from_u128(wide << 40), a * a * a);
+        assert_eq!(
+            FieldElement::from_u128(u128::MAX).value() as u128,
+            u128::MAX % FIELD_PRIME as u128
+        );
+        assert_eq!(
+            FieldElement::from_u128(FIELD_PRIME as u128 * 3 + 5),
+            FieldElement::new(5)
+        );
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let elements = hash_to_field(b"seed", 10);
+        assert_eq!(elements.len(), 10);
+        assert_eq!(elements, hash_to_field(b"seed", 10));
+        assert!(elements.iter().all(|e| e.value() < FIELD_PRIME));
+        // A longer draw extends a shorter one
+        assert_eq!(hash_to_field(b"seed", 3), elements[..3]);
+        assert_ne!(hash_to_field(b"other", 10), elements);
+        assert!(hash_to_field(b"seed", 0).is_empty());
+
+        let wide: Vec<NttField> = hash_to_fp(b"seed", 10);
+        assert!(wide.iter().all(|e| e.value() < GOLDILOCKS_PRIME));
+    }
+
+    #[test]
+    fn test_try_div() {
+        let a = FieldElement::new(10);
+        let b = FieldElement::new(4);
+        assert_eq!(a.try_div(b), Ok(a / b));
+        assert_eq!(a.try_div(b).unwrap() * b, a);
+        assert_eq!(a.try_div(FieldElement::zero()), Err(FieldError::DivByZero));
+    }
+
+    #[test]
+    fn test_sum_and_product() {
+        let elems: Vec<FieldElement> = (1..=5).map(FieldElement::new).collect();
+
+        assert_eq!(
+            elems.iter().copied().sum::<FieldElement>(),
+            FieldElement::new(15)
+        );
+        assert_eq!(
+            elems.iter().product::<FieldElement>(),
+            FieldElement::new(120)
+        );
+
+        let empty: Vec<FieldElement> = vec![];
+        assert_eq!(empty.iter().sum::<FieldElement>(), FieldElement::zero());
+        assert_eq!(
+            empty.into_iter().product::<FieldElement>(),
+            FieldElement::one()
+        );
+    }
+
+    // Small prime; the module's `Goldilocks` is a 64-bit prime whose sums overflow u64
+    type Small = Fp<97>;
+
+    fn check_field_axioms<const P: u64>() {
+        for _ in 0..200 {
+            let a = Fp::<P>::random();
+            let b = Fp::<P>::random();
+
+            for x in [a + b, a - b, a * b, a.pow(5)] {
+                assert!(x.value() < P);
+            }
+            assert_eq!((a + b) - b, a);
+            if b != Fp::zero() {
+                assert_eq!(a * b / b, a);
+                assert_eq!(b * b.inverse().unwrap(), Fp::one());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Non-canonical field element")]
+    fn test_non_canonical_operand_asserts() {
+        let unreduced = FieldElement { value: FIELD_PRIME };
+        let _ = unreduced + FieldElement::one();
+    }
+
+    #[test]
+    fn test_generic_moduli() {
+        check_field_axioms::<97>();
+        check_field_axioms::<0xffff_ffff_0000_0001>();
+        check_field_axioms::<FIELD_PRIME>();
+
+        assert_eq!(Small::new(96) + Small::new(5), Small::new(4));
+        assert_eq!(Small::new(3) - Small::new(5), Small::new(95));
+        assert_eq!(Small::new(200).value(), 6);
+        assert_eq!(Small::BITS, 7);
+
+        let max = Goldilocks::new(Goldilocks::MODULUS - 1);
+        assert_eq!(max + max, Goldilocks::new(Goldilocks::MODULUS - 2));
+        assert_eq!(max * max, Goldilocks::one());
+        assert_eq!(Goldilocks::BITS, 64);
+
+        assert_eq!(Mersenne31::BITS, 31);
+        assert_eq!(FieldElement::MODULUS, FIELD_PRIME);
+    }
+
+    #[test]
+    fn test_pack_many_generic_widths() {
+        let small: Vec<Small> = (0..50).map(Small::new).collect();
+        let packed = Small::pack_many(&small);
+        assert_eq!(packed.len(), (50 * 7usize).div_ceil(8));
+        assert_eq!(Small::unpack_many(&packed, 50).unwrap(), small);
+
+        let wide: Vec<Goldilocks> = (0..50).map(|_| Goldilocks::random()).collect();
+        let packed = Goldilocks::pack_many(&wide);
+        assert_eq!(packed.len(), 50 * 8);
+        assert_eq!(Goldilocks::unpack_many(&packed, 50).unwrap(), wide);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_reduces_untrusted_values() {
+        let a = FieldElement::new(12345);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, "12345");
+        assert_eq!(serde_json::from_str::<FieldElement>(&json).unwrap(), a);
+
+        let unreduced = (FIELD_PRIME + 7).to_string();
+        let decoded: FieldElement = serde_json::from_str(&unreduced).unwrap();
+        assert_eq!(decoded, FieldElement::new(7));
+        assert!(decoded.value() < FIELD_PRIME);
+    }
+
+    #[test]
+    fn test_random_is_canonical() {
+        for _ in 0..1000 {
+            assert!(FieldElement::random().value() < FIELD_PRIME);
+        }
+    }
+
+    #[test]
+    fn test_pack_many_round_trip() {
+        let elements: Vec<FieldElement> = (0..1000).map(|_| FieldElement::random()).collect();
+
+        let packed = FieldElement::pack_many(&elements);
+        let naive_len = elements.len() * 8;
+        assert_eq!(packed.len(), FieldElement::packed_len(elements.len()));
+
+        // 31 of every 64 bits are used, allowing one byte of rounding
+        let expected = naive_len * 31 / 64;
+        assert!(packed.len() >= expected && packed.len() <= expected + 1);
+
+        let unpacked = FieldElement::unpack_many(&packed, elements.len()).unwrap();
+        assert_eq!(unpacked, elements);
+    }
+
+    #[test]
+    fn test_pack_many_edge_values() {
+        let elements = vec![
+            FieldElement::zero(),
+            FieldElement::new(FIELD_PRIME - 1),
+            FieldElement::one(),
+        ];
+        let packed = FieldElement::pack_many(&elements);
+        assert_eq!(FieldElement::unpack_many(&packed, 3).unwrap(), elements);
+        assert!(FieldElement::pack_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_unpack_many_rejects_malformed_input() {
+        let packed = FieldElement::pack_many(&[FieldElement::new(7), FieldElement::new(9)]);
+        assert_eq!(
+            FieldElement::unpack_many(&packed[..packed.len() - 1], 2),
+            Err(FieldError::LengthMismatch {
+                expected: packed.len(),
+                actual: packed.len() - 1
+            })
+        );
+
+        // All ones in the low 31 bits encodes FIELD_PRIME itself
+        let bytes = [0xff, 0xff, 0xff, 0x7f];
+        assert_eq!(
+            FieldElement::unpack_many(&bytes, 1),
+            Err(FieldError::NonCanonical(FIELD_PRIME))
+        );
     }
 }
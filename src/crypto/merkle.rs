@@ -1,17 +1,106 @@
 // src/crypto/merkle.rs
 
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use sha2::{Digest, Sha256};
-use std::fmt;
+
+const DEFAULT_ARITY: usize = 2;
+const NODE_SIZE: usize = 32;
+// Lengths SHA-256 output can be truncated to for every node. 16 bytes keeps
+// 128-bit second-preimage resistance but only 64-bit collision resistance.
+pub const DIGEST_LENS: [usize; 3] = [16, 20, 32];
+// Largest node vector an allocation can hold
+const MAX_NODES: usize = isize::MAX as usize / core::mem::size_of::<Vec<u8>>();
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MerkleError {
+    #[error("serialized tree ends unexpectedly")]
+    Truncated,
+    #[error("invalid tree arity {0}")]
+    InvalidArity(usize),
+    #[error("unsupported digest length {0}")]
+    InvalidDigestLen(usize),
+    #[error("tree layout needs {expected} nodes but {actual} were given")]
+    NodeCountMismatch { expected: usize, actual: usize },
+    #[error("{0} leaves exceed the maximum tree size")]
+    TooManyLeaves(usize),
+    #[error("leaf index {index} out of range for {leaf_count} leaves")]
+    IndexOutOfRange { index: usize, leaf_count: usize },
+}
 
 #[derive(Clone)]
 pub struct MerkleTree {
     nodes: Vec<Vec<u8>>,
     leaf_count: usize,
+    arity: usize,
+    // Bytes of SHA-256 output kept for leaves and internal nodes
+    digest_len: usize,
+    // Copy of nodes[0], kept so `root()` doesn't allocate; zero past `digest_len`
+    root: [u8; 32],
+}
+
+// Authentication data for several leaves at once. Sibling nodes that the
+// verifier can compute itself from other opened leaves are left out, so
+// neighbouring indices share most of their path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProof {
+    indices: Vec<usize>,
+    depth: usize,
+    arity: usize,
+    nodes: Vec<Vec<u8>>,
+}
+
+impl MultiProof {
+    // Opened leaf indices, ascending and without duplicates. Leaves passed
+    // to `verify_multiproof` must be in this order.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+// Single-leaf proof in which siblings that are pure zero padding are marked
+// by one bit each instead of carrying their hash. The verifier recomputes
+// them: padding at level k is the hash of `arity` level k - 1 paddings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactProof {
+    // Sibling slots in `generate_proof` order
+    slots: usize,
+    // Bit i (least significant first) is set if slot i is padding
+    padding: Vec<u8>,
+    // Hashes of the remaining slots, in order
+    siblings: Vec<Vec<u8>>,
+}
+
+impl CompactProof {
+    pub fn siblings(&self) -> &[Vec<u8>] {
+        &self.siblings
+    }
+
+    pub fn padding_count(&self) -> usize {
+        self.padding.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    // Bytes of hashes and bitmap carried
+    pub fn size_bytes(&self) -> usize {
+        self.padding.len() + self.siblings.iter().map(Vec::len).sum::<usize>()
+    }
+
+    fn is_padding(&self, slot: usize) -> bool {
+        self.padding[slot / 8] >> (slot % 8) & 1 == 1
+    }
 }
 
 impl fmt::Debug for MerkleTree {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "MerkleTree {{")?;
+        writeln!(f, "  arity: {}", self.arity)?;
+        writeln!(f, "  digest_len: {}", self.digest_len)?;
         writeln!(f, "  leaf_count: {}", self.leaf_count)?;
         writeln!(f, "  nodes: [")?;
         for (i, node) in self.nodes.iter().enumerate() {
@@ -25,37 +114,354 @@ impl fmt::Debug for MerkleTree {
 
 impl MerkleTree {
     pub fn new(leaves: Vec<Vec<u8>>) -> Self {
-        if leaves.is_empty() {
+        Self::with_arity(leaves, DEFAULT_ARITY)
+    }
+
+    // Like `new`, but rejects leaf counts whose padded layout can't be allocated
+    pub fn try_new(leaves: Vec<Vec<u8>>) -> Result<Self, MerkleError> {
+        Self::layout(leaves.len(), DEFAULT_ARITY)?;
+        Ok(Self::new(leaves))
+    }
+
+    // Each internal node hashes the concatenation of `arity` children. Wider
+    // trees are shallower, at the cost of `arity - 1` siblings per proof level.
+    pub fn with_arity(leaves: Vec<Vec<u8>>, arity: usize) -> Self {
+        assert!(arity >= 2, "Merkle tree arity must be at least 2");
+        let leaf_hashes = leaves
+            .iter()
+            .map(|leaf| Self::hash_leaf(leaf, NODE_SIZE))
+            .collect();
+        Self::from_leaf_hashes(leaf_hashes, arity, NODE_SIZE)
+    }
+
+    // Binary tree keeping only the first `digest_len` bytes of every hash,
+    // one of `DIGEST_LENS`. Proofs shrink accordingly; verify them against
+    // `root()`, whose length tells the verifier the digest size.
+    pub fn with_digest_len(leaves: Vec<Vec<u8>>, digest_len: usize) -> Self {
+        assert!(
+            DIGEST_LENS.contains(&digest_len),
+            "Unsupported Merkle digest length"
+        );
+        let leaf_hashes = leaves
+            .iter()
+            .map(|leaf| Self::hash_leaf(leaf, digest_len))
+            .collect();
+        Self::from_leaf_hashes(leaf_hashes, DEFAULT_ARITY, digest_len)
+    }
+
+    // Leaves as contiguous fixed-size arrays, such as serialized field
+    // elements, so no allocation per leaf is needed. Same tree as `new` over
+    // the same bytes.
+    pub fn from_fixed_leaves<const N: usize>(leaves: &[[u8; N]]) -> Self {
+        let leaf_hashes = leaves
+            .iter()
+            .map(|leaf| Self::hash_leaf(leaf, NODE_SIZE))
+            .collect();
+        Self::from_leaf_hashes(leaf_hashes, DEFAULT_ARITY, NODE_SIZE)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn from_fixed_leaves_parallel<const N: usize>(leaves: &[[u8; N]]) -> Self {
+        let leaf_hashes = leaves
+            .par_iter()
+            .map(|leaf| Self::hash_leaf(leaf, NODE_SIZE))
+            .collect();
+        Self::build(
+            leaf_hashes,
+            DEFAULT_ARITY,
+            NODE_SIZE,
+            Self::hash_levels_parallel,
+        )
+    }
+
+    // Same tree as `new`, with leaves and each level hashed on the rayon pool
+    #[cfg(feature = "parallel")]
+    pub fn new_parallel(leaves: Vec<Vec<u8>>) -> Self {
+        Self::with_arity_parallel(leaves, DEFAULT_ARITY)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn with_arity_parallel(leaves: Vec<Vec<u8>>, arity: usize) -> Self {
+        assert!(arity >= 2, "Merkle tree arity must be at least 2");
+        let leaf_hashes = leaves
+            .par_iter()
+            .map(|leaf| Self::hash_leaf(leaf, NODE_SIZE))
+            .collect();
+        Self::build(leaf_hashes, arity, NODE_SIZE, Self::hash_levels_parallel)
+    }
+
+    // Leaf-level width and position of the first leaf for a tree with
+    // `leaf_count` leaves, checking the node count stays allocatable
+    fn layout(leaf_count: usize, arity: usize) -> Result<(usize, usize), MerkleError> {
+        let too_many = MerkleError::TooManyLeaves(leaf_count);
+        let mut width: usize = 1;
+        while width < leaf_count {
+            width = width.checked_mul(arity).ok_or(too_many.clone())?;
+        }
+        let first_leaf = (width - 1) / (arity - 1);
+        match first_leaf.checked_add(width) {
+            Some(nodes) if nodes <= MAX_NODES => Ok((width, first_leaf)),
+            _ => Err(too_many),
+        }
+    }
+
+    fn from_leaf_hashes(leaf_hashes: Vec<Vec<u8>>, arity: usize, digest_len: usize) -> Self {
+        Self::build(leaf_hashes, arity, digest_len, Self::hash_levels)
+    }
+
+    // Lays out the leaves and fills in the internal nodes with `hash_levels`
+    fn build(
+        leaf_hashes: Vec<Vec<u8>>,
+        arity: usize,
+        digest_len: usize,
+        hash_levels: fn(&mut [Vec<u8>], usize, usize, usize),
+    ) -> Self {
+        if leaf_hashes.is_empty() {
             return Self {
-                nodes: vec![vec![0u8; 32]],
+                nodes: vec![vec![0u8; digest_len]],
                 leaf_count: 0,
+                arity,
+                digest_len,
+                root: [0u8; 32],
             };
         }
 
-        let leaf_count = leaves.len();
-        let total_nodes = 2 * leaf_count - 1;
-        let mut nodes = vec![vec![0u8; 32]; total_nodes];
+        let leaf_count = leaf_hashes.len();
+        // Pad the leaf level to a power of the arity with zero nodes so that
+        // every node's position matches the index arithmetic in `verify_proof`
+        let (width, first_leaf) =
+            Self::layout(leaf_count, arity).unwrap_or_else(|err| panic!("{}", err));
+        let mut nodes = vec![vec![0u8; digest_len]; first_leaf + width];
 
-        // Copy leaves into the second half of the array
-        for (i, leaf) in leaves.into_iter().enumerate() {
-            let mut hasher = Sha256::new();
-            hasher.update(&leaf);
-            nodes[leaf_count - 1 + i] = hasher.finalize().to_vec();
+        // Copy leaves into the last level of the array
+        for (i, hash) in leaf_hashes.into_iter().enumerate() {
+            nodes[first_leaf + i] = hash;
         }
 
-        // Build internal nodes
-        for i in (0..leaf_count - 1).rev() {
-            let mut hasher = Sha256::new();
-            hasher.update(&nodes[2 * i + 1]); // Left child
-            hasher.update(&nodes[2 * i + 2]); // Right child
-            nodes[i] = hasher.finalize().to_vec();
+        hash_levels(&mut nodes, first_leaf, arity, digest_len);
+
+        let mut tree = Self {
+            nodes,
+            leaf_count,
+            arity,
+            digest_len,
+            root: [0u8; 32],
+        };
+        tree.cache_root();
+        tree
+    }
+
+    // Build internal nodes
+    fn hash_levels(nodes: &mut [Vec<u8>], first_leaf: usize, arity: usize, digest_len: usize) {
+        for i in (0..first_leaf).rev() {
+            let children = arity * i + 1..=arity * i + arity;
+            nodes[i] = Self::hash_node(&nodes[children], digest_len);
         }
+    }
 
-        Self { nodes, leaf_count }
+    // Levels are contiguous in `nodes` and the children of consecutive
+    // parents are consecutive, so each level hashes as independent chunks of
+    // the level below it
+    #[cfg(feature = "parallel")]
+    fn hash_levels_parallel(
+        nodes: &mut [Vec<u8>],
+        first_leaf: usize,
+        arity: usize,
+        digest_len: usize,
+    ) {
+        let mut level_start = first_leaf;
+        while level_start > 0 {
+            let parent_start = (level_start - 1) / arity;
+            let (parents, children) = nodes.split_at_mut(level_start);
+            parents[parent_start..]
+                .par_iter_mut()
+                .zip(children.par_chunks(arity))
+                .for_each(|(parent, children)| *parent = Self::hash_node(children, digest_len));
+            level_start = parent_start;
+        }
+    }
+
+    fn cache_root(&mut self) {
+        self.root = [0u8; 32];
+        self.root[..self.digest_len].copy_from_slice(&self.nodes[0]);
     }
 
-    pub fn root(&self) -> Vec<u8> {
-        self.nodes[0].clone()
+    fn hash_leaf(leaf: &[u8], digest_len: usize) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(leaf);
+        hasher.finalize()[..digest_len].to_vec()
+    }
+
+    fn hash_node<T: AsRef<[u8]>>(children: &[T], digest_len: usize) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        for child in children {
+            hasher.update(child.as_ref());
+        }
+        hasher.finalize()[..digest_len].to_vec()
+    }
+
+    // Add a leaf after the current last one. While there is a free padding
+    // slot only the path from that slot to the root is rehashed; a full tree
+    // is rebuilt one level deeper, which keeps appends amortized O(log n)
+    pub fn append(&mut self, leaf: Vec<u8>) {
+        let hash = Self::hash_leaf(&leaf, self.digest_len);
+
+        if self.leaf_count == self.width() && self.leaf_count > 0 {
+            let first_leaf = self.first_leaf();
+            let mut leaf_hashes: Vec<Vec<u8>> =
+                self.nodes[first_leaf..first_leaf + self.leaf_count].to_vec();
+            leaf_hashes.push(hash);
+            *self = Self::from_leaf_hashes(leaf_hashes, self.arity, self.digest_len);
+            return;
+        }
+
+        let position = self.first_leaf() + self.leaf_count;
+        self.nodes[position] = hash;
+        self.leaf_count += 1;
+        self.rehash_path(position);
+    }
+
+    // Replace an existing leaf, rehashing only its path to the root
+    pub fn update_leaf(&mut self, index: usize, new_leaf: Vec<u8>) -> Result<(), MerkleError> {
+        if index >= self.leaf_count {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                leaf_count: self.leaf_count,
+            });
+        }
+        let position = self.first_leaf() + index;
+        self.nodes[position] = Self::hash_leaf(&new_leaf, self.digest_len);
+        self.rehash_path(position);
+        Ok(())
+    }
+
+    // Recompute every ancestor of the node at `position`, then the cached root
+    fn rehash_path(&mut self, mut position: usize) {
+        while position > 0 {
+            position = (position - 1) / self.arity;
+            let children = self.arity * position + 1..=self.arity * position + self.arity;
+            self.nodes[position] = Self::hash_node(&self.nodes[children], self.digest_len);
+        }
+        self.cache_root();
+    }
+
+    // Joins two binary trees of the same depth under a new root hashing their
+    // roots, reusing every existing node. The left tree must be full so the
+    // leaves stay contiguous: left leaf `i` keeps index `i` and right leaf `j`
+    // becomes `left.len() + j`, with the other tree's root appended to its proof.
+    // The right tree must have a leaf: an empty one has no leaf level to
+    // interleave, only the placeholder root.
+    pub fn concat(left: &MerkleTree, right: &MerkleTree) -> MerkleTree {
+        assert!(
+            left.arity == 2 && right.arity == 2,
+            "Only binary trees can be concatenated"
+        );
+        assert!(
+            left.digest_len == right.digest_len,
+            "Concatenated trees must have the same digest length"
+        );
+        assert!(right.leaf_count > 0, "The right tree must not be empty");
+        assert!(
+            left.nodes.len() == right.nodes.len(),
+            "Concatenated trees must have the same depth"
+        );
+        assert!(
+            left.leaf_count > 0 && left.leaf_count == left.width(),
+            "The left tree must be full"
+        );
+
+        let mut nodes = Vec::with_capacity(1 + 2 * left.nodes.len());
+        nodes.push(Self::hash_node(
+            &[left.root(), right.root()],
+            left.digest_len,
+        ));
+        // Interleave the two heaps level by level
+        let (mut start, mut width) = (0, 1);
+        while start < left.nodes.len() {
+            nodes.extend_from_slice(&left.nodes[start..start + width]);
+            nodes.extend_from_slice(&right.nodes[start..start + width]);
+            start += width;
+            width *= 2;
+        }
+
+        let mut tree = Self {
+            nodes,
+            leaf_count: left.leaf_count + right.leaf_count,
+            arity: 2,
+            digest_len: left.digest_len,
+            root: [0u8; 32],
+        };
+        tree.cache_root();
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    // Number of allocated nodes, including zero padding
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Levels between the leaves and the root; 0 for empty and single-leaf trees
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut width = 1;
+        while width < self.width() {
+            width *= self.arity;
+            depth += 1;
+        }
+        depth
+    }
+
+    pub fn digest_len(&self) -> usize {
+        self.digest_len
+    }
+
+    // The root at its digest length, as the proof verifiers expect it
+    pub fn root(&self) -> &[u8] {
+        &self.root[..self.digest_len]
+    }
+
+    // The root as the fixed array commitments are stored as. Only a tree
+    // at the full 32-byte digest has one; a truncated tree panics.
+    pub fn full_root(&self) -> [u8; 32] {
+        assert!(
+            self.digest_len == NODE_SIZE,
+            "A truncated tree's root is only its `root()` bytes"
+        );
+        self.root
+    }
+
+    // Number of leaf slots including zero padding
+    fn width(&self) -> usize {
+        ((self.arity - 1) * self.nodes.len() + 1) / self.arity
+    }
+
+    // Position of the first leaf in `nodes`
+    fn first_leaf(&self) -> usize {
+        (self.width() - 1) / (self.arity - 1)
+    }
+
+    // Hashes of the real leaves in order, without the zero padding
+    pub fn leaf_hashes(&self) -> impl Iterator<Item = &[u8]> {
+        let first = self.first_leaf();
+        self.nodes[first..first + self.leaf_count]
+            .iter()
+            .map(|node| node.as_slice())
     }
 
     pub fn generate_proof(&self, index: usize) -> Vec<Vec<u8>> {
@@ -64,52 +470,309 @@ impl MerkleTree {
         }
 
         let mut proof = Vec::new();
-        let mut current = self.leaf_count - 1 + index;
+        let mut current = self.first_leaf() + index;
 
         while current > 0 {
-            // If we're a left child, get right sibling, and vice versa
-            let sibling = if current % 2 == 0 {
-                current - 1
-            } else {
-                current + 1
-            };
-
-            if sibling < self.nodes.len() {
-                proof.push(self.nodes[sibling].clone());
+            // Collect every other child of our parent, left to right
+            let parent = (current - 1) / self.arity;
+            let first_child = self.arity * parent + 1;
+            for sibling in first_child..first_child + self.arity {
+                if sibling != current {
+                    proof.push(self.nodes[sibling].clone());
+                }
             }
 
             // Move up to parent
-            current = (current - 1) / 2;
+            current = parent;
         }
 
         proof
     }
 
     pub fn verify_proof(root: &[u8], leaf: &[u8], proof: &[Vec<u8>], index: usize) -> bool {
-        let mut hasher = Sha256::new();
-        hasher.update(leaf);
-        let mut current = hasher.finalize().to_vec();
+        Self::verify_proof_with_arity(root, leaf, proof, index, DEFAULT_ARITY)
+    }
+
+    // `proof` holds `arity - 1` siblings per level, as produced by
+    // `generate_proof`. Hashes are truncated to the length of `root`.
+    pub fn verify_proof_with_arity(
+        root: &[u8],
+        leaf: &[u8],
+        proof: &[Vec<u8>],
+        index: usize,
+        arity: usize,
+    ) -> bool {
+        let digest_len = root.len();
+        if arity < 2 || !proof.len().is_multiple_of(arity - 1) || !DIGEST_LENS.contains(&digest_len)
+        {
+            return false;
+        }
+
+        let mut current = Self::hash_leaf(leaf, digest_len);
         let mut current_index = index;
 
-        for proof_element in proof {
-            let mut hasher = Sha256::new();
-            if current_index % 2 == 0 {
-                hasher.update(&current);
-                hasher.update(proof_element);
+        for siblings in proof.chunks(arity - 1) {
+            let position = current_index % arity;
+            let mut children: Vec<&[u8]> = siblings.iter().map(|s| s.as_slice()).collect();
+            children.insert(position, &current);
+            current = Self::hash_node(&children, digest_len);
+            current_index /= arity;
+        }
+
+        current == root
+    }
+
+    // Hash of an all-padding subtree at each level, leaves first
+    fn padding_hashes(levels: usize, arity: usize, digest_len: usize) -> Vec<Vec<u8>> {
+        let mut padding = vec![vec![0u8; digest_len]];
+        for level in 1..levels {
+            let children = vec![padding[level - 1].as_slice(); arity];
+            padding.push(Self::hash_node(&children, digest_len));
+        }
+        padding
+    }
+
+    // `generate_proof` with padding siblings reduced to a bit. For a tree
+    // without padding the siblings are exactly those of `generate_proof`.
+    pub fn generate_compact_proof(&self, index: usize) -> CompactProof {
+        let proof = self.generate_proof(index);
+        let padding_hashes = Self::padding_hashes(self.depth(), self.arity, self.digest_len);
+
+        let mut padding = vec![0u8; proof.len().div_ceil(8)];
+        let mut siblings = Vec::new();
+        for (slot, sibling) in proof.iter().enumerate() {
+            if *sibling == padding_hashes[slot / (self.arity - 1)] {
+                padding[slot / 8] |= 1 << (slot % 8);
             } else {
-                hasher.update(proof_element);
-                hasher.update(&current);
+                siblings.push(sibling.clone());
             }
-            current = hasher.finalize().to_vec();
-            current_index /= 2;
         }
 
-        current == root
+        CompactProof {
+            slots: proof.len(),
+            padding,
+            siblings,
+        }
+    }
+
+    pub fn verify_compact_proof(
+        root: &[u8],
+        leaf: &[u8],
+        proof: &CompactProof,
+        index: usize,
+    ) -> bool {
+        Self::verify_compact_proof_with_arity(root, leaf, proof, index, DEFAULT_ARITY)
+    }
+
+    // Expands the padding bits back into hashes and checks the result as a
+    // `verify_proof_with_arity` path
+    pub fn verify_compact_proof_with_arity(
+        root: &[u8],
+        leaf: &[u8],
+        proof: &CompactProof,
+        index: usize,
+        arity: usize,
+    ) -> bool {
+        let digest_len = root.len();
+        if arity < 2
+            || !DIGEST_LENS.contains(&digest_len)
+            || !proof.slots.is_multiple_of(arity - 1)
+            || proof.padding.len() != proof.slots.div_ceil(8)
+            || proof.padding_count() + proof.siblings.len() != proof.slots
+        {
+            return false;
+        }
+
+        let padding_hashes = Self::padding_hashes(proof.slots / (arity - 1), arity, digest_len);
+        let mut siblings = proof.siblings.iter();
+        let mut path = Vec::with_capacity(proof.slots);
+        for slot in 0..proof.slots {
+            if proof.is_padding(slot) {
+                path.push(padding_hashes[slot / (arity - 1)].clone());
+            } else if let Some(sibling) = siblings.next() {
+                path.push(sibling.clone());
+            } else {
+                return false;
+            }
+        }
+
+        Self::verify_proof_with_arity(root, leaf, &path, index, arity)
+    }
+
+    // Indices past the last leaf are ignored, just as `generate_proof`
+    // returns an empty path for them
+    pub fn generate_multiproof(&self, indices: &[usize]) -> MultiProof {
+        let mut indices: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|&i| i < self.leaf_count)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let first_leaf = self.first_leaf();
+        let depth = self.depth();
+
+        let mut nodes = Vec::new();
+        let mut level: Vec<usize> = indices.iter().map(|&i| first_leaf + i).collect();
+
+        while level.first().is_some_and(|&pos| pos > 0) {
+            let mut parents = Vec::with_capacity(level.len());
+            let mut i = 0;
+            while i < level.len() {
+                let parent = (level[i] - 1) / self.arity;
+                let first_child = self.arity * parent + 1;
+                // Only siblings that aren't themselves being opened go in the proof
+                for child in first_child..first_child + self.arity {
+                    if level.get(i) == Some(&child) {
+                        i += 1;
+                    } else {
+                        nodes.push(self.nodes[child].clone());
+                    }
+                }
+                parents.push(parent);
+            }
+            level = parents;
+        }
+
+        MultiProof {
+            indices,
+            depth,
+            arity: self.arity,
+            nodes,
+        }
+    }
+
+    pub fn verify_multiproof(root: &[u8], leaves: &[Vec<u8>], proof: &MultiProof) -> bool {
+        let (arity, digest_len) = (proof.arity, root.len());
+        if arity < 2
+            || leaves.is_empty()
+            || leaves.len() != proof.indices.len()
+            || !DIGEST_LENS.contains(&digest_len)
+        {
+            return false;
+        }
+
+        let Some(width) = u32::try_from(proof.depth)
+            .ok()
+            .and_then(|depth| arity.checked_pow(depth))
+        else {
+            return false;
+        };
+        if proof.indices.windows(2).any(|w| w[0] >= w[1])
+            || proof.indices[proof.indices.len() - 1] >= width
+        {
+            return false;
+        }
+
+        let first_leaf = (width - 1) / (arity - 1);
+        let mut level: Vec<(usize, Vec<u8>)> = proof
+            .indices
+            .iter()
+            .zip(leaves)
+            .map(|(&i, leaf)| (first_leaf + i, Self::hash_leaf(leaf, digest_len)))
+            .collect();
+        let mut siblings = proof.nodes.iter();
+
+        for _ in 0..proof.depth {
+            let mut parents = Vec::with_capacity(level.len());
+            let mut i = 0;
+            while i < level.len() {
+                let parent = (level[i].0 - 1) / arity;
+                let first_child = arity * parent + 1;
+                let mut children: Vec<&[u8]> = Vec::with_capacity(arity);
+                for child in first_child..first_child + arity {
+                    match level.get(i) {
+                        Some((pos, hash)) if *pos == child => {
+                            children.push(hash);
+                            i += 1;
+                        }
+                        _ => match siblings.next() {
+                            Some(sibling) => children.push(sibling),
+                            None => return false,
+                        },
+                    }
+                }
+                parents.push((parent, Self::hash_node(&children, digest_len)));
+            }
+            level = parents;
+        }
+
+        siblings.next().is_none() && level.len() == 1 && level[0].1 == root
+    }
+
+    // Snapshot layout: leaf count, arity, node count and digest length as
+    // little-endian u64s, followed by every node (padding included) as
+    // `digest_len` raw bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 + self.nodes.len() * self.digest_len);
+        out.extend_from_slice(&(self.leaf_count as u64).to_le_bytes());
+        out.extend_from_slice(&(self.arity as u64).to_le_bytes());
+        out.extend_from_slice(&(self.nodes.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(self.digest_len as u64).to_le_bytes());
+        for node in &self.nodes {
+            out.extend_from_slice(node);
+        }
+        out
+    }
+
+    // Restores a snapshot without rehashing. The node count must match the
+    // layout implied by the leaf count and arity; node contents are trusted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MerkleTree, MerkleError> {
+        let read_u64 = |offset: usize| -> Result<usize, MerkleError> {
+            let chunk = bytes
+                .get(offset..offset + 8)
+                .ok_or(MerkleError::Truncated)?;
+            let value = u64::from_le_bytes(chunk.try_into().unwrap());
+            usize::try_from(value).map_err(|_| MerkleError::Truncated)
+        };
+
+        let leaf_count = read_u64(0)?;
+        let arity = read_u64(8)?;
+        let node_count = read_u64(16)?;
+        let digest_len = read_u64(24)?;
+
+        if arity < 2 {
+            return Err(MerkleError::InvalidArity(arity));
+        }
+        if !DIGEST_LENS.contains(&digest_len) {
+            return Err(MerkleError::InvalidDigestLen(digest_len));
+        }
+        let expected = if leaf_count == 0 {
+            1
+        } else {
+            let (width, first_leaf) = Self::layout(leaf_count, arity)?;
+            first_leaf + width
+        };
+        if node_count != expected {
+            return Err(MerkleError::NodeCountMismatch {
+                expected,
+                actual: node_count,
+            });
+        }
+
+        let body = &bytes[32..];
+        if body.len() != node_count * digest_len {
+            return Err(MerkleError::Truncated);
+        }
+
+        let nodes: Vec<Vec<u8>> = body.chunks(digest_len).map(|c| c.to_vec()).collect();
+        let mut tree = Self {
+            nodes,
+            leaf_count,
+            arity,
+            digest_len,
+            root: [0u8; 32],
+        };
+        tree.cache_root();
+        Ok(tree)
     }
 
     // Helper function to visualize the tree (useful for debugging)
+    #[cfg(feature = "std")]
     pub fn print_tree(&self) {
         println!("\nMerkle Tree Structure:");
+        println!("Arity: {}", self.arity);
         println!("Leaf count: {}", self.leaf_count);
         println!("Total nodes: {}", self.nodes.len());
 
@@ -129,13 +792,13 @@ impl MerkleTree {
                 }
             }
             printed += level_size;
-            level_size *= 2;
+            level_size *= self.arity;
             level += 1;
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -159,7 +822,7 @@ mod tests {
         hasher.update(&leaf);
         let expected_hash = hasher.finalize().to_vec();
 
-        assert_eq!(tree.root(), expected_hash);
+        assert_eq!(tree.root().to_vec(), expected_hash);
         assert_eq!(tree.leaf_count, 1);
     }
 
@@ -183,7 +846,7 @@ mod tests {
         hasher.update(&hash2);
         let root_hash = hasher.finalize().to_vec();
 
-        assert_eq!(tree.root(), root_hash);
+        assert_eq!(tree.root().to_vec(), root_hash);
 
         // Verify proofs for both leaves
         let proof0 = tree.generate_proof(0);
@@ -193,6 +856,57 @@ mod tests {
         assert!(MerkleTree::verify_proof(&root_hash, &leaf2, &proof1, 1));
     }
 
+    #[test]
+    fn test_from_fixed_leaves() {
+        let fixed: Vec<[u8; 8]> = (0..13u64).map(|i| (i * 7919).to_le_bytes()).collect();
+        let leaves: Vec<Vec<u8>> = fixed.iter().map(|leaf| leaf.to_vec()).collect();
+
+        let tree = MerkleTree::from_fixed_leaves(&fixed);
+        assert_eq!(tree.root(), MerkleTree::new(leaves.clone()).root());
+        assert_eq!(tree.len(), 13);
+        let proof = tree.generate_proof(12);
+        assert!(MerkleTree::verify_proof(
+            tree.root(),
+            &leaves[12],
+            &proof,
+            12
+        ));
+
+        let empty: &[[u8; 8]] = &[];
+        assert_eq!(
+            MerkleTree::from_fixed_leaves(empty).root(),
+            MerkleTree::new(vec![]).root()
+        );
+        #[cfg(feature = "parallel")]
+        assert_eq!(
+            MerkleTree::from_fixed_leaves_parallel(&fixed).root(),
+            tree.root()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let leaves: Vec<Vec<u8>> = (0..1024u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let parallel = MerkleTree::new_parallel(leaves.clone());
+        assert_eq!(parallel.root(), tree.root());
+        assert_eq!(parallel.nodes, tree.nodes);
+
+        // Padded and k-ary layouts too
+        for (count, arity) in [(1000, 2), (37, 3), (100, 4), (1, 2)] {
+            let leaves = leaves[..count].to_vec();
+            assert_eq!(
+                MerkleTree::with_arity_parallel(leaves.clone(), arity).nodes,
+                MerkleTree::with_arity(leaves, arity).nodes
+            );
+        }
+        assert_eq!(
+            MerkleTree::new_parallel(vec![]).root(),
+            MerkleTree::new(vec![]).root()
+        );
+    }
+
     #[test]
     fn test_four_leaves() {
         let leaves: Vec<Vec<u8>> = (0..4).map(|i| vec![i as u8]).collect();
@@ -202,7 +916,7 @@ mod tests {
         tree.print_tree();
 
         let root = tree.root();
-        println!("\nRoot hash: {}", bytes_to_hex(&root));
+        println!("\nRoot hash: {}", bytes_to_hex(root));
 
         // Test proofs for all leaves
         for (i, leaf) in leaves.iter().enumerate() {
@@ -215,7 +929,7 @@ mod tests {
             }
 
             assert!(
-                MerkleTree::verify_proof(&root, leaf, &proof, i),
+                MerkleTree::verify_proof(root, leaf, &proof, i),
                 "Proof verification failed for leaf {}",
                 i
             );
@@ -233,17 +947,549 @@ mod tests {
 
         // Try to verify with wrong leaf
         let wrong_leaf = vec![3u8];
-        assert!(!MerkleTree::verify_proof(&root, &wrong_leaf, &proof, 0));
+        assert!(!MerkleTree::verify_proof(root, &wrong_leaf, &proof, 0));
 
         // Try to verify with wrong index
-        assert!(!MerkleTree::verify_proof(&root, &leaves[0], &proof, 1));
+        assert!(!MerkleTree::verify_proof(root, &leaves[0], &proof, 1));
 
         // Try to verify with modified proof
         let mut bad_proof = proof.clone();
         if !bad_proof.is_empty() {
             bad_proof[0] = vec![0u8; 32];
         }
-        assert!(!MerkleTree::verify_proof(&root, &leaves[0], &bad_proof, 0));
+        assert!(!MerkleTree::verify_proof(root, &leaves[0], &bad_proof, 0));
+    }
+
+    #[test]
+    fn test_non_power_of_two_leaves() {
+        for count in [3usize, 5, 6, 7] {
+            let leaves: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::new(leaves.clone());
+            let root = tree.root();
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.generate_proof(i);
+                assert!(
+                    MerkleTree::verify_proof(root, leaf, &proof, i),
+                    "Proof verification failed for leaf {} of {}",
+                    i,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_append_matches_batch_construction() {
+        let leaves: Vec<Vec<u8>> = (0..9).map(|i| vec![i as u8, 0xab]).collect();
+        let mut tree = MerkleTree::new(vec![]);
+        assert!(tree.is_empty());
+
+        for (n, leaf) in leaves.iter().enumerate() {
+            tree.append(leaf.clone());
+            assert_eq!(tree.len(), n + 1);
+
+            let batch = MerkleTree::new(leaves[..=n].to_vec());
+            assert_eq!(
+                tree.root(),
+                batch.root(),
+                "Root mismatch after {} appends",
+                n + 1
+            );
+
+            // Every proof, old and new, still verifies against the updated root
+            let root = tree.root();
+            for (i, l) in leaves[..=n].iter().enumerate() {
+                let proof = tree.generate_proof(i);
+                assert!(MerkleTree::verify_proof(root, l, &proof, i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiproof_matches_single_proofs() {
+        let leaves: Vec<Vec<u8>> = (0..16).map(|i| vec![i as u8]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+
+        for indices in [vec![4, 5, 6, 7], vec![0, 9, 15], vec![3], vec![12, 2, 12]] {
+            let multiproof = tree.generate_multiproof(&indices);
+            let opened: Vec<Vec<u8>> = multiproof
+                .indices()
+                .iter()
+                .map(|&i| leaves[i].clone())
+                .collect();
+            assert!(MerkleTree::verify_multiproof(root, &opened, &multiproof));
+
+            let single_nodes: usize = multiproof
+                .indices()
+                .iter()
+                .map(|&i| {
+                    let proof = tree.generate_proof(i);
+                    assert!(MerkleTree::verify_proof(root, &leaves[i], &proof, i));
+                    proof.len()
+                })
+                .sum();
+            assert!(multiproof.node_count() <= single_nodes);
+
+            // Swapping in a wrong leaf fails just like a single proof would
+            let mut wrong = opened.clone();
+            wrong[0] = vec![0xff];
+            assert!(!MerkleTree::verify_multiproof(root, &wrong, &multiproof));
+        }
+
+        // Four adjacent leaves share everything below their common subtree
+        let adjacent = tree.generate_multiproof(&[4, 5, 6, 7]);
+        assert_eq!(adjacent.node_count(), 2);
+        assert_eq!(adjacent.indices(), &[4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_compact_proof_omits_padding() {
+        let leaves: Vec<Vec<u8>> = (0..5).map(|i| vec![i as u8]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let full = tree.generate_proof(i);
+            let compact = tree.generate_compact_proof(i);
+            assert!(MerkleTree::verify_compact_proof(root, leaf, &compact, i));
+            assert!(!MerkleTree::verify_compact_proof(
+                root,
+                &[0xff],
+                &compact,
+                i
+            ));
+            assert_eq!(
+                compact.padding_count() + compact.siblings().len(),
+                full.len()
+            );
+        }
+
+        // Leaf 4's sibling leaf and sibling pair are both padding
+        let full = tree.generate_proof(4);
+        let compact = tree.generate_compact_proof(4);
+        assert_eq!(compact.padding_count(), 2);
+        let full_bytes: usize = full.iter().map(Vec::len).sum();
+        assert!(compact.size_bytes() < full_bytes);
+
+        // Without padding nothing is left out
+        let full_tree = MerkleTree::new((0..8).map(|i| vec![i as u8]).collect());
+        let compact = full_tree.generate_compact_proof(4);
+        assert_eq!(compact.padding_count(), 0);
+        assert_eq!(compact.siblings(), full_tree.generate_proof(4).as_slice());
+    }
+
+    #[test]
+    fn test_multiproof_padded_tree() {
+        let leaves: Vec<Vec<u8>> = (0..5).map(|i| vec![i as u8]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let multiproof = tree.generate_multiproof(&[1, 4, 7]);
+
+        assert_eq!(multiproof.indices(), &[1, 4]);
+        assert!(MerkleTree::verify_multiproof(
+            tree.root(),
+            &[leaves[1].clone(), leaves[4].clone()],
+            &multiproof
+        ));
+    }
+
+    #[test]
+    fn test_default_arity_is_binary() {
+        let leaves: Vec<Vec<u8>> = (0..6).map(|i| vec![i as u8]).collect();
+        let binary = MerkleTree::with_arity(leaves.clone(), 2);
+        assert_eq!(MerkleTree::new(leaves).root(), binary.root());
+        assert_eq!(binary.arity(), 2);
+    }
+
+    #[test]
+    fn test_four_ary_tree() {
+        let leaves: Vec<Vec<u8>> = (0..16).map(|i| vec![i as u8]).collect();
+        let tree = MerkleTree::with_arity(leaves.clone(), 4);
+        let root = tree.root();
+
+        // 16 leaves in a 4-ary tree: root, 4 internal nodes, 16 leaves
+        assert_eq!(tree.nodes.len(), 21);
+        let expected_root = MerkleTree::hash_node(&tree.nodes[1..=4], NODE_SIZE);
+        assert_eq!(root.to_vec(), expected_root);
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert_eq!(proof.len(), 2 * 3, "Two levels of three siblings");
+            assert!(MerkleTree::verify_proof_with_arity(
+                root, leaf, &proof, i, 4
+            ));
+            assert!(!MerkleTree::verify_proof_with_arity(
+                root,
+                leaf,
+                &proof,
+                (i + 1) % 16,
+                4
+            ));
+            assert!(!MerkleTree::verify_proof(root, leaf, &proof, i));
+        }
+    }
+
+    #[test]
+    fn test_eight_ary_padded_tree() {
+        let leaves: Vec<Vec<u8>> = (0..10).map(|i| vec![i as u8]).collect();
+        let tree = MerkleTree::with_arity(leaves.clone(), 8);
+        let root = tree.root();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert!(MerkleTree::verify_proof_with_arity(
+                root, leaf, &proof, i, 8
+            ));
+        }
+    }
+
+    #[test]
+    fn test_k_ary_append_and_multiproof() {
+        let leaves: Vec<Vec<u8>> = (0..20).map(|i| vec![i as u8]).collect();
+        let mut tree = MerkleTree::with_arity(vec![], 4);
+        for leaf in &leaves {
+            tree.append(leaf.clone());
+        }
+        let batch = MerkleTree::with_arity(leaves.clone(), 4);
+        assert_eq!(tree.root(), batch.root());
+
+        let multiproof = tree.generate_multiproof(&[1, 2, 3, 17]);
+        let opened: Vec<Vec<u8>> = multiproof
+            .indices()
+            .iter()
+            .map(|&i| leaves[i].clone())
+            .collect();
+        assert!(MerkleTree::verify_multiproof(
+            tree.root(),
+            &opened,
+            &multiproof
+        ));
+    }
+
+    #[test]
+    fn test_size_accessors() {
+        let empty = MerkleTree::new(vec![]);
+        assert_eq!(empty.depth(), 0);
+        assert_eq!(empty.leaf_count(), 0);
+        assert_eq!(empty.node_count(), 1);
+
+        let single = MerkleTree::new(vec![vec![1u8]]);
+        assert_eq!(single.depth(), 0);
+        assert_eq!(single.leaf_count(), 1);
+        assert_eq!(single.node_count(), 1);
+
+        let eight = MerkleTree::new((0..8).map(|i| vec![i as u8]).collect());
+        assert_eq!(eight.depth(), 3);
+        assert_eq!(eight.leaf_count(), 8);
+        assert_eq!(eight.node_count(), 15);
+        assert_eq!(eight.generate_proof(0).len(), eight.depth());
+
+        // Padding counts towards the allocation but not the leaf count
+        let five = MerkleTree::new((0..5).map(|i| vec![i as u8]).collect());
+        assert_eq!(five.depth(), 3);
+        assert_eq!(five.leaf_count(), 5);
+        assert_eq!(five.node_count(), 15);
+
+        let four_ary = MerkleTree::with_arity((0..16).map(|i| vec![i as u8]).collect(), 4);
+        assert_eq!(four_ary.depth(), 2);
+        assert_eq!(four_ary.node_count(), 21);
+    }
+
+    #[test]
+    fn test_cached_root_matches_recomputation() {
+        let mut tree = MerkleTree::new((0..6).map(|i| vec![i as u8]).collect());
+        let recomputed: [u8; 32] = Sha256::new()
+            .chain_update(&tree.nodes[1])
+            .chain_update(&tree.nodes[2])
+            .finalize()
+            .into();
+        assert_eq!(tree.root(), recomputed);
+
+        tree.append(vec![42]);
+        let recomputed: [u8; 32] = Sha256::new()
+            .chain_update(&tree.nodes[1])
+            .chain_update(&tree.nodes[2])
+            .finalize()
+            .into();
+        assert_eq!(tree.root(), recomputed);
+        assert_eq!(tree.root(), tree.nodes[0].as_slice());
+    }
+
+    #[test]
+    fn test_update_leaf() {
+        let mut leaves: Vec<Vec<u8>> = (0..8).map(|i| vec![i]).collect();
+        let mut tree = MerkleTree::new(leaves.clone());
+
+        tree.update_leaf(2, vec![42]).unwrap();
+        leaves[2] = vec![42];
+        let rebuilt = MerkleTree::new(leaves.clone());
+        assert_eq!(tree.root(), rebuilt.root());
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert!(MerkleTree::verify_proof(tree.root(), leaf, &proof, i));
+        }
+
+        // Padded k-ary trees too
+        let mut leaves: Vec<Vec<u8>> = (0..6).map(|i| vec![i]).collect();
+        let mut tree = MerkleTree::with_arity(leaves.clone(), 4);
+        tree.update_leaf(5, vec![9, 9]).unwrap();
+        leaves[5] = vec![9, 9];
+        assert_eq!(tree.root(), MerkleTree::with_arity(leaves, 4).root());
+
+        assert_eq!(
+            tree.update_leaf(6, vec![]),
+            Err(MerkleError::IndexOutOfRange {
+                index: 6,
+                leaf_count: 6
+            })
+        );
+        assert!(MerkleTree::new(vec![]).update_leaf(0, vec![1]).is_err());
+    }
+
+    #[test]
+    fn test_truncated_digests() {
+        let leaves: Vec<Vec<u8>> = (0..6u8).map(|i| vec![i; 4]).collect();
+        let tree = MerkleTree::with_digest_len(leaves.clone(), 16);
+        let full = MerkleTree::new(leaves.clone());
+        assert_eq!(tree.digest_len(), 16);
+        assert_eq!(tree.root().len(), 16);
+        assert_eq!(full.root(), full.root());
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert!(proof.iter().all(|node| node.len() == 16));
+            assert!(MerkleTree::verify_proof(tree.root(), leaf, &proof, i));
+            // Checked at the full length, the truncated proof doesn't verify
+            assert!(!MerkleTree::verify_proof(full.root(), leaf, &proof, i));
+        }
+
+        let multiproof = tree.generate_multiproof(&[1, 4]);
+        let opened = [leaves[1].clone(), leaves[4].clone()];
+        assert!(MerkleTree::verify_multiproof(
+            tree.root(),
+            &opened,
+            &multiproof
+        ));
+
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.digest_len(), 16);
+        assert_eq!(restored.root(), tree.root());
+    }
+
+    #[test]
+    fn test_root_verifies_at_every_digest_len() {
+        let leaves: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 3]).collect();
+        for digest_len in DIGEST_LENS {
+            let tree = MerkleTree::with_digest_len(leaves.clone(), digest_len);
+            assert_eq!(tree.root().len(), digest_len);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.generate_proof(i);
+                assert!(MerkleTree::verify_proof(tree.root(), leaf, &proof, i));
+            }
+
+            let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+            assert_eq!(restored.root(), tree.root());
+            let proof = restored.generate_proof(2);
+            assert!(MerkleTree::verify_proof(
+                restored.root(),
+                &leaves[2],
+                &proof,
+                2
+            ));
+        }
+        let full = MerkleTree::new(leaves);
+        assert_eq!(full.full_root(), full.root());
+    }
+
+    #[test]
+    #[should_panic(expected = "A truncated tree's root is only its `root()` bytes")]
+    fn test_truncated_tree_has_no_full_root() {
+        MerkleTree::with_digest_len(vec![vec![1]], 20).full_root();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported Merkle digest length")]
+    fn test_unsupported_digest_len() {
+        MerkleTree::with_digest_len(vec![vec![1]], 8);
+    }
+
+    #[test]
+    fn test_concat() {
+        let left_leaves: Vec<Vec<u8>> = (0..4).map(|i| vec![i]).collect();
+        let right_leaves: Vec<Vec<u8>> = (4..7).map(|i| vec![i]).collect();
+        let left = MerkleTree::new(left_leaves.clone());
+        let right = MerkleTree::new(right_leaves.clone());
+
+        let merged = MerkleTree::concat(&left, &right);
+        assert_eq!(merged.len(), 7);
+        assert_eq!(merged.depth(), left.depth() + 1);
+
+        // Child proofs extend by the other subtree's root
+        let mut proof = left.generate_proof(1);
+        proof.push(right.root().to_vec());
+        assert!(MerkleTree::verify_proof(
+            merged.root(),
+            &left_leaves[1],
+            &proof,
+            1
+        ));
+        assert_eq!(proof, merged.generate_proof(1));
+
+        let mut proof = right.generate_proof(2);
+        proof.push(left.root().to_vec());
+        assert!(MerkleTree::verify_proof(
+            merged.root(),
+            &right_leaves[2],
+            &proof,
+            6
+        ));
+        assert!(!MerkleTree::verify_proof(
+            merged.root(),
+            &right_leaves[2],
+            &proof,
+            2
+        ));
+
+        // Same tree as building over all the leaves at once
+        let all: Vec<Vec<u8>> = left_leaves.into_iter().chain(right_leaves).collect();
+        let rebuilt = MerkleTree::new(all);
+        assert_eq!(merged.root(), rebuilt.root());
+        assert_eq!(merged.nodes, rebuilt.nodes);
+        let restored = MerkleTree::from_bytes(&merged.to_bytes()).unwrap();
+        assert_eq!(restored.root(), merged.root());
+
+        // Single leaves join into a two-leaf tree that round-trips as well
+        let pair = MerkleTree::concat(
+            &MerkleTree::new(vec![vec![1]]),
+            &MerkleTree::new(vec![vec![2]]),
+        );
+        assert_eq!(pair.root(), MerkleTree::new(vec![vec![1], vec![2]]).root());
+        assert!(MerkleTree::from_bytes(&pair.to_bytes()).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "The right tree must not be empty")]
+    fn test_concat_rejects_empty_right_tree() {
+        // Both are a single node, so the depths alone would match
+        MerkleTree::concat(&MerkleTree::new(vec![vec![1]]), &MerkleTree::new(vec![]));
+    }
+
+    #[test]
+    #[should_panic(expected = "The left tree must be full")]
+    fn test_concat_rejects_partial_left_tree() {
+        let leaves = |n: u8| (0..n).map(|i| vec![i]).collect::<Vec<_>>();
+        MerkleTree::concat(&MerkleTree::new(leaves(3)), &MerkleTree::new(leaves(4)));
+    }
+
+    #[test]
+    fn test_try_new() {
+        let leaves: Vec<Vec<u8>> = (0..6).map(|i| vec![i]).collect();
+        let tree = MerkleTree::try_new(leaves.clone()).unwrap();
+        assert_eq!(tree.root(), MerkleTree::new(leaves).root());
+        assert!(MerkleTree::try_new(vec![]).is_ok());
+
+        // Leaf counts too large to build are caught from the count alone
+        for count in [usize::MAX, usize::MAX / 2 + 2, MAX_NODES] {
+            assert_eq!(
+                MerkleTree::layout(count, 2),
+                Err(MerkleError::TooManyLeaves(count))
+            );
+        }
+        assert!(MerkleTree::layout(MAX_NODES / 4, 2).is_ok());
+    }
+
+    #[test]
+    fn test_leaf_hashes() {
+        assert_eq!(MerkleTree::new(vec![]).leaf_hashes().count(), 0);
+
+        for (count, arity) in [(1, 2), (5, 2), (8, 2), (10, 4)] {
+            let leaves: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8, 0xaa]).collect();
+            let tree = MerkleTree::with_arity(leaves.clone(), arity);
+
+            let hashes: Vec<&[u8]> = tree.leaf_hashes().collect();
+            assert_eq!(hashes.len(), tree.leaf_count());
+            assert_eq!(hashes[0], Sha256::digest(&leaves[0]).as_slice());
+            for (hash, leaf) in hashes.iter().zip(&leaves) {
+                assert_eq!(*hash, MerkleTree::hash_leaf(leaf, NODE_SIZE).as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        for (count, arity) in [(0, 2), (1, 2), (5, 2), (8, 2), (10, 4)] {
+            let leaves: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::with_arity(leaves.clone(), arity);
+
+            let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+            assert_eq!(restored.root(), tree.root());
+            assert_eq!(restored.leaf_count(), count);
+            assert_eq!(restored.arity(), arity);
+            assert_eq!(restored.nodes, tree.nodes);
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = restored.generate_proof(i);
+                assert!(MerkleTree::verify_proof_with_arity(
+                    restored.root(),
+                    leaf,
+                    &proof,
+                    i,
+                    arity
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_deserialization_rejects_corrupt_input() {
+        let tree = MerkleTree::new((0..5).map(|i| vec![i as u8]).collect());
+        let bytes = tree.to_bytes();
+
+        // Every truncation fails cleanly
+        for len in 0..bytes.len() {
+            assert!(MerkleTree::from_bytes(&bytes[..len]).is_err());
+        }
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(
+            MerkleTree::from_bytes(&extra).unwrap_err(),
+            MerkleError::Truncated
+        );
+
+        // 9 leaves pad to a 16-wide leaf level, which needs 31 nodes
+        let mut wrong_count = bytes.clone();
+        wrong_count[..8].copy_from_slice(&9u64.to_le_bytes());
+        assert_eq!(
+            MerkleTree::from_bytes(&wrong_count).unwrap_err(),
+            MerkleError::NodeCountMismatch {
+                expected: 31,
+                actual: 15
+            }
+        );
+
+        let mut bad_arity = bytes.clone();
+        bad_arity[8..16].copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            MerkleTree::from_bytes(&bad_arity).unwrap_err(),
+            MerkleError::InvalidArity(1)
+        );
+
+        let mut bad_digest = bytes.clone();
+        bad_digest[24..32].copy_from_slice(&8u64.to_le_bytes());
+        assert_eq!(
+            MerkleTree::from_bytes(&bad_digest).unwrap_err(),
+            MerkleError::InvalidDigestLen(8)
+        );
+
+        let mut huge = bytes.clone();
+        huge[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            MerkleTree::from_bytes(&huge).unwrap_err(),
+            MerkleError::TooManyLeaves(usize::MAX)
+        );
     }
 
     #[test]
@@ -261,8 +1507,8 @@ mod tests {
             assert_eq!(proof1, proof2);
 
             // Both proofs should verify
-            assert!(MerkleTree::verify_proof(&root, leaf, &proof1, i));
-            assert!(MerkleTree::verify_proof(&root, leaf, &proof2, i));
+            assert!(MerkleTree::verify_proof(root, leaf, &proof1, i));
+            assert!(MerkleTree::verify_proof(root, leaf, &proof2, i));
         }
     }
 }
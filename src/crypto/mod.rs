@@ -1,2 +1,39 @@
 pub mod field;
 pub mod merkle;
+pub mod poly;
+pub mod transcript;
+
+// Built only when `std` is disabled, to check the crypto path works on core + alloc
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use super::field::{FieldElement, FIELD_PRIME};
+    use super::merkle::MerkleTree;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_field_arithmetic_without_std() {
+        let a = FieldElement::new(123_456_789);
+        let b = FieldElement::new(FIELD_PRIME + 5);
+
+        assert_eq!(b, FieldElement::new(5));
+        assert_eq!((a + b) - b, a);
+        assert_eq!(a * b / b, a);
+        assert_eq!(a * a.inverse().unwrap(), FieldElement::one());
+        assert_eq!(a.pow(FIELD_PRIME - 1), FieldElement::one());
+
+        let packed = FieldElement::pack_many(&[a, b]);
+        assert_eq!(FieldElement::unpack_many(&packed, 2).unwrap(), [a, b]);
+    }
+
+    #[test]
+    fn test_merkle_proofs_without_std() {
+        let leaves: Vec<Vec<u8>> = (0..5u8).map(|i| alloc::vec![i]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert!(MerkleTree::verify_proof(root, leaf, &proof, i));
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// src/crypto/poly.rs
+
+use super::field::{FieldElement, FieldError};
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Mul};
+
+// Polynomial in coefficient form, lowest degree first. Trailing zero
+// coefficients are trimmed so equal polynomials compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    coeffs: Vec<FieldElement>,
+}
+
+impl Polynomial {
+    pub fn new(mut coeffs: Vec<FieldElement>) -> Self {
+        while coeffs.last() == Some(&FieldElement::zero()) {
+            coeffs.pop();
+        }
+        Polynomial { coeffs }
+    }
+
+    pub fn zero() -> Self {
+        Polynomial { coeffs: Vec::new() }
+    }
+
+    pub fn coefficients(&self) -> &[FieldElement] {
+        &self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    // Highest power with a non-zero coefficient; constants, including the
+    // zero polynomial, have degree 0
+    pub fn degree(&self) -> usize {
+        self.coeffs.len().saturating_sub(1)
+    }
+
+    // Horner's rule
+    pub fn eval(&self, x: FieldElement) -> FieldElement {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(FieldElement::zero(), |acc, &c| acc * x + c)
+    }
+
+    // The polynomial of degree < n through the n points (domain[i], values[i]).
+    // Domain points must be distinct. O(n²). Panics on a length mismatch.
+    pub fn interpolate(domain: &[FieldElement], values: &[FieldElement]) -> Self {
+        assert_eq!(
+            domain.len(),
+            values.len(),
+            "Interpolation needs one value per domain point"
+        );
+        let n = domain.len();
+
+        // Vanishing polynomial of the domain, Π (x - x_j)
+        let mut vanishing = vec![FieldElement::one()];
+        for &xj in domain {
+            vanishing.insert(0, FieldElement::zero());
+            for k in 0..vanishing.len() - 1 {
+                vanishing[k] = vanishing[k] - xj * vanishing[k + 1];
+            }
+        }
+
+        let mut coeffs = vec![FieldElement::zero(); n];
+        for (i, &xi) in domain.iter().enumerate() {
+            // Divide out (x - x_i), then scale so the basis polynomial is 1 at x_i
+            let mut basis = vec![FieldElement::zero(); n];
+            let mut carry = FieldElement::zero();
+            for k in (0..n).rev() {
+                carry = vanishing[k + 1] + carry * xi;
+                basis[k] = carry;
+            }
+            let denom = domain
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &xj)| xi - xj)
+                .product::<FieldElement>();
+            let scale = values[i] / denom;
+            for (c, b) in coeffs.iter_mut().zip(basis) {
+                *c = *c + scale * b;
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (mut long, short) = if self.coeffs.len() >= other.coeffs.len() {
+            (self.coeffs, other.coeffs)
+        } else {
+            (other.coeffs, self.coeffs)
+        };
+        for (c, s) in long.iter_mut().zip(short) {
+            *c = *c + s;
+        }
+        Polynomial::new(long)
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut coeffs = vec![FieldElement::zero(); self.coeffs.len() + other.coeffs.len() - 1];
+        for (i, &a) in self.coeffs.iter().enumerate() {
+            for (j, &b) in other.coeffs.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j] + a * b;
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+// Value at `x` of the unique polynomial of degree < n through the n points
+// (domain[i], values[i]). Domain points must be distinct. O(n²).
+// Panics if `domain` and `values` have different lengths.
+pub fn lagrange_interpolate(
+    domain: &[FieldElement],
+    values: &[FieldElement],
+    x: FieldElement,
+) -> FieldElement {
+    try_lagrange_interpolate(domain, values, x)
+        .expect("Interpolation domain points must be distinct")
+}
+
+// `lagrange_interpolate` for domains that may repeat a point, which makes a
+// Lagrange denominator zero
+pub fn try_lagrange_interpolate(
+    domain: &[FieldElement],
+    values: &[FieldElement],
+    x: FieldElement,
+) -> Result<FieldElement, FieldError> {
+    assert_eq!(
+        domain.len(),
+        values.len(),
+        "Interpolation needs one value per domain point"
+    );
+
+    if let Some(i) = domain.iter().position(|&xi| xi == x) {
+        return Ok(values[i]);
+    }
+
+    let mut result = FieldElement::zero();
+    for (i, (&xi, &yi)) in domain.iter().zip(values).enumerate() {
+        let mut num = FieldElement::one();
+        let mut den = FieldElement::one();
+        for (j, &xj) in domain.iter().enumerate() {
+            if i != j {
+                num = num * (x - xj);
+                den = den * (xi - xj);
+            }
+        }
+        result = result + (yi * num).try_div(den)?;
+    }
+    Ok(result)
+}
+
+// Whether the codeword `evaluations` over `domain` is the evaluation of a
+// polynomial of degree at most `degree_bound`, found by interpolating
+// through every point. O(n²), so meant for tests and sanity checks rather
+// than verification. Domain points must be distinct.
+pub fn is_low_degree(
+    evaluations: &[FieldElement],
+    domain: &[FieldElement],
+    degree_bound: usize,
+) -> bool {
+    Polynomial::interpolate(domain, evaluations).degree() <= degree_bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::field::hash_to_field;
+
+    fn poly(coeffs: &[u64]) -> Polynomial {
+        Polynomial::new(coeffs.iter().map(|&c| FieldElement::new(c)).collect())
+    }
+
+    #[test]
+    fn test_polynomial_mul_and_eval() {
+        // (x + 1)(x + 2) = x² + 3x + 2
+        let product = poly(&[1, 1]) * poly(&[2, 1]);
+        assert_eq!(product, poly(&[2, 3, 1]));
+        assert_eq!(product.degree(), 2);
+
+        for (x, expected) in [(0, 2), (1, 6), (5, 42)] {
+            assert_eq!(
+                product.eval(FieldElement::new(x)),
+                FieldElement::new(expected)
+            );
+        }
+        let x = FieldElement::new(1_000_000);
+        assert_eq!(
+            product.eval(x),
+            (x + FieldElement::one()) * (x + FieldElement::new(2))
+        );
+
+        assert!((product.clone() * Polynomial::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_polynomial_add() {
+        assert_eq!(poly(&[1, 2]) + poly(&[3, 4, 5]), poly(&[4, 6, 5]));
+
+        // Cancelling leading terms lowers the degree
+        let minus_one = FieldElement::zero() - FieldElement::one();
+        let minus_x2 = Polynomial::new(vec![FieldElement::zero(), FieldElement::zero(), minus_one]);
+        let sum = poly(&[1, 0, 1]) + minus_x2;
+        assert_eq!(sum, poly(&[1]));
+        assert_eq!(sum.degree(), 0);
+        assert_eq!(poly(&[0, 0]), Polynomial::zero());
+    }
+
+    #[test]
+    fn test_interpolate_recovers_coefficients() {
+        let f = poly(&[7, 0, 3, 11]);
+        let domain: Vec<FieldElement> = (20..24).map(FieldElement::new).collect();
+        let values: Vec<FieldElement> = domain.iter().map(|&x| f.eval(x)).collect();
+        assert_eq!(Polynomial::interpolate(&domain, &values), f);
+    }
+
+    #[test]
+    fn test_interpolate_cubic() {
+        // f(x) = 2x³ - x + 5
+        let f = |x: FieldElement| FieldElement::new(2) * x * x * x - x + FieldElement::new(5);
+        let domain: Vec<FieldElement> = [1, 4, 9, 10].into_iter().map(FieldElement::new).collect();
+        let values: Vec<FieldElement> = domain.iter().map(|&x| f(x)).collect();
+
+        for x in [0, 2, 3, 100, 123_456] {
+            let x = FieldElement::new(x);
+            assert_eq!(lagrange_interpolate(&domain, &values, x), f(x));
+        }
+        assert_eq!(lagrange_interpolate(&domain, &values, domain[2]), values[2]);
+        assert_eq!(
+            lagrange_interpolate(&[], &[], FieldElement::one()),
+            FieldElement::zero()
+        );
+    }
+
+    #[test]
+    fn test_is_low_degree() {
+        let f = poly(&[5, 1, 0, 9]);
+        let domain: Vec<FieldElement> = (0..16).map(FieldElement::new).collect();
+        let codeword: Vec<FieldElement> = domain.iter().map(|&x| f.eval(x)).collect();
+        assert!(is_low_degree(&codeword, &domain, 3));
+        assert!(is_low_degree(&codeword, &domain, 8));
+        assert!(!is_low_degree(&codeword, &domain, 2));
+
+        let noise = hash_to_field(b"not a codeword", domain.len());
+        assert!(!is_low_degree(&noise, &domain, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "one value per domain point")]
+    fn test_interpolate_length_mismatch() {
+        let domain = [FieldElement::new(1), FieldElement::new(2)];
+        lagrange_interpolate(&domain, &[FieldElement::one()], FieldElement::zero());
+    }
+}
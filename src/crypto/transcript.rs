@@ -0,0 +1,100 @@
+// src/crypto/transcript.rs
+
+use super::field::{hash_to_fp, Fp};
+use sha2::{Digest, Sha256};
+
+// Fiat-Shamir transcript: a SHA-256 hash chain over everything the prover has
+// committed to so far. Prover and verifier absorb the same messages in the
+// same order and so draw the same challenges.
+#[derive(Clone, Debug)]
+pub struct Transcript {
+    state: [u8; 32],
+}
+
+impl Transcript {
+    // The label separates transcripts of different protocols
+    pub fn new(label: &[u8]) -> Self {
+        Transcript {
+            state: Sha256::digest(label).into(),
+        }
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.state);
+        hasher.update(data);
+        self.state = hasher.finalize().into();
+    }
+
+    // Each challenge advances the chain, so consecutive draws differ
+    pub fn challenge_bytes(&mut self) -> [u8; 32] {
+        self.absorb(b"challenge");
+        self.state
+    }
+
+    fn challenge_u64(&mut self) -> u64 {
+        let bytes = self.challenge_bytes();
+        u64::from_le_bytes(bytes[..8].try_into().unwrap())
+    }
+
+    // Uniform field element, expanded from the next challenge by `hash_to_field`
+    pub fn challenge_field<const P: u64>(&mut self) -> Fp<P> {
+        hash_to_fp(&self.challenge_bytes(), 1)[0]
+    }
+
+    // Uniform index in `0..bound`
+    pub fn challenge_index(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Challenge bound must be non-zero");
+        let bound = bound as u64;
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let candidate = self.challenge_u64();
+            if candidate < limit {
+                return (candidate % bound) as usize;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::field::{FieldElement, FIELD_PRIME};
+
+    #[test]
+    fn test_transcript_is_deterministic() {
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"test");
+        a.absorb(b"commitment");
+        b.absorb(b"commitment");
+
+        let x: FieldElement = a.challenge_field();
+        let y: FieldElement = b.challenge_field();
+        assert_eq!(x, y);
+        assert!(x.value() < FIELD_PRIME);
+        assert_eq!(a.challenge_index(10), b.challenge_index(10));
+
+        // Later challenges differ from earlier ones, and other messages or
+        // labels lead to other challenges
+        assert_ne!(
+            a.challenge_bytes(),
+            Transcript::new(b"test").challenge_bytes()
+        );
+        let mut c = Transcript::new(b"test");
+        c.absorb(b"other");
+        let mut d = Transcript::new(b"other");
+        d.absorb(b"commitment");
+        let z: FieldElement = c.challenge_field();
+        let w: FieldElement = d.challenge_field();
+        assert_ne!(x, z);
+        assert_ne!(x, w);
+    }
+
+    #[test]
+    fn test_challenge_index_in_range() {
+        let mut transcript = Transcript::new(b"test");
+        for bound in 1..50 {
+            assert!(transcript.challenge_index(bound) < bound);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// src/error.rs
+
+// One error type for the whole crate. Each subsystem keeps its own precise
+// error enum; they all convert into `Error`, so code mixing field, Merkle,
+// accumulator and consensus calls can use `?` throughout.
+//
+// Every public fallible function of the accumulator and consensus modules
+// returns `Result<T>`, with the subsystem enum inside. The exceptions:
+// - the field and Merkle primitives return `FieldError` and `MerkleError`.
+//   The modules above call them internally and map their failures to their
+//   own variants (a zero divisor during evaluation is a `DegenerateDomain`),
+//   which the precise type keeps exhaustive.
+// - predicates return `bool`: `Accumulator::verify` and
+//   `Consensus::validate_block` by their trait signatures, and
+//   `validate_genesis` and `verify_state_proof` alongside them. Each has a
+//   `Result<()>` twin saying which check failed: `check`, `check_block`,
+//   `check_genesis` and `check_state_proof`. `validate_block_with_parent`
+//   has none; `validate_chain` reports the same checks with the index.
+// - `verify_batch` returns the index of the first failing proof, which is
+//   a position rather than an error.
+
+#[cfg(feature = "std")]
+use crate::accumulator::reed_solomon::{ProofError, RSError, VerifyError};
+#[cfg(feature = "std")]
+use crate::consensus::density::{ChainError, ConfigError};
+use crate::crypto::field::FieldError;
+use crate::crypto::merkle::MerkleError;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("field: {0}")]
+    Field(#[from] FieldError),
+    #[error("merkle: {0}")]
+    Merkle(#[from] MerkleError),
+    #[cfg(feature = "std")]
+    #[error("accumulator: {0}")]
+    Accumulator(#[from] RSError),
+    #[cfg(feature = "std")]
+    #[error("proof verification: {0}")]
+    Verify(#[from] VerifyError),
+    #[cfg(feature = "std")]
+    #[error("proof encoding: {0}")]
+    Proof(#[from] ProofError),
+    #[cfg(feature = "std")]
+    #[error("chain: {0}")]
+    Chain(#[from] ChainError),
+    #[cfg(feature = "std")]
+    #[error("consensus config: {0}")]
+    Config(#[from] ConfigError),
+    #[cfg(feature = "std")]
+    #[error("block slot {slot} is past the latest acceptable slot {latest}")]
+    FutureBlock { slot: u64, latest: u64 },
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::field::FieldElement;
+    use crate::crypto::merkle::MerkleTree;
+    #[cfg(feature = "std")]
+    use alloc::vec;
+
+    #[test]
+    fn test_field_error_maps_to_field() {
+        let err: Error = FieldElement::new(1)
+            .try_div(FieldElement::new(0))
+            .unwrap_err()
+            .into();
+        assert_eq!(err, Error::Field(FieldError::DivByZero));
+    }
+
+    #[test]
+    fn test_merkle_error_maps_to_merkle() {
+        let err: Error = MerkleTree::from_bytes(&[1, 2]).unwrap_err().into();
+        assert_eq!(err, Error::Merkle(MerkleError::Truncated));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_accumulator_entry_points_return_accumulator_variants() {
+        use crate::accumulator::reed_solomon::{RSProof, ReedSolomonAccumulator};
+        use crate::accumulator::Accumulator;
+
+        assert_eq!(
+            ReedSolomonAccumulator::with_domain(vec![]).err(),
+            Some(Error::Accumulator(RSError::EmptyDomain))
+        );
+        assert_eq!(
+            RSProof::from_bytes(&[1, 2, 3]),
+            Err(Error::Proof(ProofError::Truncated))
+        );
+
+        let mut acc = ReedSolomonAccumulator::new();
+        let proof = acc.accumulate(vec![FieldElement::new(1)]);
+        let other = ReedSolomonAccumulator::new();
+        assert_eq!(
+            other.verify_against_root(&proof, &[7; 32]),
+            Err(Error::Verify(VerifyError::RootMismatch))
+        );
+        // `check` is the reason behind `verify`
+        assert!(!other.verify(&proof));
+        assert_eq!(
+            other.check(&proof),
+            Err(Error::Verify(VerifyError::RootMismatch))
+        );
+        assert_eq!(acc.check(&proof), Ok(()));
+        assert_eq!(ReedSolomonAccumulator::verify_proof(&proof), Ok(()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_consensus_errors_map_to_consensus_variants() {
+        use crate::consensus::density::{Block, DensityConsensus};
+
+        assert_eq!(
+            DensityConsensus::builder().slot_duration(0).build().err(),
+            Some(Error::Config(ConfigError::ZeroSlotDuration))
+        );
+
+        let consensus = DensityConsensus::new();
+        let block = Block::new([1; 32], 0, 0, vec![FieldElement::new(1)]);
+        assert_eq!(consensus.check_block(&block), Ok(()));
+        let invalid_genesis = Err(Error::Chain(ChainError::InvalidGenesis));
+        assert_eq!(
+            consensus.validate_chain(std::slice::from_ref(&block)),
+            invalid_genesis
+        );
+        assert!(!consensus.validate_genesis(&block));
+        assert_eq!(consensus.check_genesis(&block), invalid_genesis);
+        assert!(matches!(
+            Block::from_bytes(&[0; 8]),
+            Err(Error::Proof(ProofError::Truncated))
+        ));
+
+        let late = Block::new([0; 32], 0, u64::MAX, vec![FieldElement::new(1)]);
+        assert!(matches!(
+            consensus.check_block(&late),
+            Err(Error::FutureBlock { slot: u64::MAX, .. })
+        ));
+    }
+}
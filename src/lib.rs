@@ -1,13 +1,27 @@
 // src/lib.rs
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod accumulator;
+#[cfg(feature = "std")]
 pub mod consensus;
 pub mod crypto;
+pub mod error;
 
 // Re-export commonly used items
+#[cfg(feature = "std")]
 pub use accumulator::{reed_solomon::ReedSolomonAccumulator, Accumulator};
+#[cfg(feature = "std")]
 pub use consensus::{
-    density::{Block, DensityConsensus},
+    clock::{Clock, SystemClock},
+    density::{
+        Block, ChainError, ConfigError, DensityConsensus, DensityConsensusBuilder, DensityTracker,
+        ForkDecision,
+    },
     Consensus,
 };
-pub use crypto::field::FieldElement;
+pub use crypto::field::{FieldElement, Fp, Mersenne31, NttField};
+pub use error::Error;
@@ -0,0 +1,68 @@
+// tests/verify_allocations.rs
+
+#![cfg(feature = "std")]
+
+use endgame::crypto::merkle::MerkleTree;
+use endgame::{FieldElement, ReedSolomonAccumulator};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocator calls per thread, so tests running in parallel don't
+// disturb each other's figures
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_verify_allocates_no_leaf_per_opening() {
+    let mut acc = ReedSolomonAccumulator::with_params(66, 16, 4);
+    let root = acc.commit((1..=10).map(FieldElement::new).collect());
+    let few = acc.open(&[1, 2]);
+    let many = acc.open(&[1, 2, 3, 4, 5, 6]);
+
+    let (few_ok, few_allocs) = allocations_during(|| few.verify(&root));
+    let (many_ok, many_allocs) = allocations_during(|| many.verify(&root));
+    assert!(few_ok.is_ok() && many_ok.is_ok());
+
+    // Cost of checking one authentication path on its own, with the leaf
+    // already on the stack. Every extra opening should cost exactly that.
+    let tree = MerkleTree::new((0..66u64).map(|i| i.to_le_bytes().to_vec()).collect());
+    let path = tree.generate_proof(3);
+    let (root, leaf) = (tree.root(), 3u64.to_le_bytes());
+    let (path_ok, path_allocs) =
+        allocations_during(|| MerkleTree::verify_proof(root, &leaf, &path, 3));
+    assert!(path_ok);
+
+    assert_eq!(many_allocs - few_allocs, 4 * path_allocs);
+}